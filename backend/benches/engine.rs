@@ -0,0 +1,50 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use backend::board::bitboard::BitBoard;
+use backend::board::evaluation::Weights;
+use backend::board::piece::Color;
+use backend::board::search::{search, SearchSettings, Threads};
+use backend::board::Board;
+
+/// middle game position with captures and kings for both sides
+const MIDDLE_GAME: &str = "W:WK10,K15,18,24,27-28:B12,16,20,K22,K25,K29";
+
+fn move_generation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("move generation");
+    for &(name, fen) in &[("initial", "W:W31-50:B1-20"), ("middle game", MIDDLE_GAME)] {
+        let (board, color) = Board::from_fen(fen).unwrap();
+        let bits = BitBoard::from(&board);
+        group.bench_function(format!("matrix {}", name), |b| {
+            b.iter(|| black_box(&board).legal_moves(color))
+        });
+        group.bench_function(format!("bitboard {}", name), |b| {
+            b.iter(|| black_box(&bits).legal_moves(color))
+        });
+    }
+    group.finish();
+}
+
+fn search_depth(c: &mut Criterion) {
+    let board = Board::new();
+    let bits = BitBoard::from(&board);
+    let settings = SearchSettings {
+        depth: 5,
+        threads: Threads::Single,
+        ..SearchSettings::default()
+    };
+
+    let weights = Weights::default();
+
+    let mut group = c.benchmark_group("search depth 5");
+    group.sample_size(10);
+    group.bench_function("matrix", |b| {
+        b.iter(|| search(black_box(&board), Color::White, &settings, &weights))
+    });
+    group.bench_function("bitboard", |b| {
+        b.iter(|| search(black_box(&bits), Color::White, &settings, &weights))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, move_generation, search_depth);
+criterion_main!(benches);
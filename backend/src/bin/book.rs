@@ -0,0 +1,57 @@
+//! Builds an opening book from games in PDN: `book [--plies N] <book> <games.pdn>...`.
+//! Every game adds its first plies, 16 unless given, each move weighing as many times
+//! as it was played. Games that can not be replayed are reported and skipped,
+//! while a file that is not valid PDN stops the build.
+
+use std::env;
+use std::fs;
+use std::process;
+
+use backend::board::book::OpeningBook;
+use backend::pdn;
+
+const USAGE: &str = "usage: book [--plies N] <book> <games.pdn>...";
+const DEFAULT_PLIES: usize = 16;
+
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let mut plies = DEFAULT_PLIES;
+    if let Some(index) = args.iter().position(|arg| arg == "--plies") {
+        plies = match args.get(index + 1).map(|plies| plies.parse::<usize>()) {
+            Some(Ok(plies)) => plies,
+            _ => exit(USAGE),
+        };
+        args.drain(index..=index + 1);
+    }
+    let (output, inputs) = match args.split_first() {
+        Some((output, inputs)) if !inputs.is_empty() => (output, inputs),
+        _ => exit(USAGE),
+    };
+
+    let mut book = OpeningBook::new();
+    let mut added = 0;
+    for input in inputs {
+        let text = fs::read_to_string(input)
+            .unwrap_or_else(|error| exit(&format!("{}: {}", input, error)));
+        let games =
+            pdn::parse_each(&text).unwrap_or_else(|error| exit(&format!("{}: {}", input, error)));
+        for (number, game) in games.into_iter().enumerate() {
+            match game.and_then(|game| game.to_game()) {
+                Ok(game) => {
+                    book.add_game(&game, plies);
+                    added += 1;
+                }
+                Err(error) => eprintln!("{} game {}: {}", input, number + 1, error),
+            }
+        }
+    }
+
+    fs::write(output, book.to_text())
+        .unwrap_or_else(|error| exit(&format!("{}: {}", output, error)));
+    println!("{} games, {} positions", added, book.len());
+}
+
+fn exit(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1)
+}
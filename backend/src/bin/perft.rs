@@ -0,0 +1,61 @@
+//! Counts the move paths of a position:
+//! `perft <depth> [fen] [--divide] [--game-type <game type>]`.
+//! Without a FEN the count starts from the initial position. With `--divide`
+//! the count below every legal move is printed too. The game type, international
+//! unless given, is named like in the API, e.g. `english`.
+
+use std::env;
+use std::process;
+use std::time::Instant;
+
+use serde::de::{value, IntoDeserializer};
+use serde::Deserialize;
+
+use backend::board::rules::GameType;
+use backend::board::Board;
+
+const USAGE: &str = "usage: perft <depth> [fen] [--divide] [--game-type <game type>]";
+
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let divide = args.iter().any(|arg| arg == "--divide");
+    args.retain(|arg| arg != "--divide");
+    let mut game_type = GameType::International;
+    if let Some(index) = args.iter().position(|arg| arg == "--game-type") {
+        let name = args
+            .get(index + 1)
+            .map(String::as_str)
+            .unwrap_or_else(|| exit(USAGE));
+        game_type = GameType::deserialize(name.into_deserializer())
+            .unwrap_or_else(|error: value::Error| exit(&error.to_string()));
+        args.drain(index..=index + 1);
+    }
+
+    let depth = match args.first().map(|depth| depth.parse::<usize>()) {
+        Some(Ok(depth)) => depth,
+        _ => exit(USAGE),
+    };
+    let (board, color) = match args.get(1) {
+        Some(fen) => {
+            Board::from_fen_for(fen, game_type).unwrap_or_else(|error| exit(&error.to_string()))
+        }
+        None => (Board::initial(game_type), game_type.rules().first),
+    };
+
+    let start = Instant::now();
+    let total = if divide {
+        let divided = board.divide(color, depth);
+        for (turn, count) in &divided {
+            println!("{} {}", turn.notation(board.size()), count);
+        }
+        divided.iter().map(|(_, count)| count).sum()
+    } else {
+        board.perft(color, depth)
+    };
+    println!("perft({}) = {} in {:.3?}", depth, total, start.elapsed());
+}
+
+fn exit(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1)
+}
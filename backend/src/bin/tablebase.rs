@@ -0,0 +1,42 @@
+//! Generates the endgame tablebase of positions with up to N pieces, at most 5:
+//! `tablebase <pieces> <output>`. The time, memory and file size it takes for each number
+//! of pieces are listed on `Tablebase::generate`.
+
+use std::env;
+use std::process;
+use std::time::Instant;
+
+use backend::board::tablebase::{Tablebase, MAX_PIECES};
+
+const USAGE: &str = "usage: tablebase <pieces> <output>";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (pieces, output) = match args.as_slice() {
+        [pieces, output] => match pieces.parse::<usize>() {
+            Ok(pieces) if pieces > MAX_PIECES => exit(&format!(
+                "tablebases of more than {} pieces are not supported",
+                MAX_PIECES
+            )),
+            Ok(pieces) if pieces >= 2 => (pieces, output),
+            _ => exit(USAGE),
+        },
+        _ => exit(USAGE),
+    };
+
+    let start = Instant::now();
+    let tablebase = Tablebase::generate(pieces);
+    tablebase
+        .save(output)
+        .unwrap_or_else(|error| exit(&format!("{}: {}", output, error)));
+    println!(
+        "{} pieces solved in {:.3?}",
+        tablebase.pieces(),
+        start.elapsed()
+    );
+}
+
+fn exit(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1)
+}
@@ -0,0 +1,532 @@
+//! Board of international draughts kept in bit masks of the 50 dark squares,
+//! bit `n - 1` standing for the square `n`. Much faster to generate moves on than `Board`,
+//! which is why the engine searches on it.
+
+use crate::board::cell::Cell;
+use crate::board::piece::{Color, Piece};
+use crate::board::position::Position;
+use crate::board::turn::Move;
+use crate::board::{zobrist, Board, MATRIX_SIZE, SQUARES};
+
+/// up-left, up-right, down-left and down-right, `up` going towards square 1
+const DIRECTIONS: [(isize, isize); 4] = [(-1, -1), (1, -1), (-1, 1), (1, 1)];
+/// directions white pawns step in; black pawns step in the other two
+const WHITE_FORWARD: [usize; 2] = [0, 1];
+const BLACK_FORWARD: [usize; 2] = [2, 3];
+/// squares 1 to 5, where white pawns get promoted
+pub(crate) const WHITE_LAST_ROW: u64 = 0b11111;
+/// squares 46 to 50, where black pawns get promoted
+pub(crate) const BLACK_LAST_ROW: u64 = 0b11111 << (SQUARES - 5);
+
+/// Squares met going from a square in each direction, up to the edge of the board
+#[derive(Clone, Copy)]
+struct Ray {
+    squares: [u8; MATRIX_SIZE - 1],
+    length: usize,
+}
+
+const RAYS: [[Ray; 4]; SQUARES] = rays();
+/// matrix index of each square, used for the Zobrist keys
+const MATRIX_INDEXES: [usize; SQUARES] = matrix_indexes();
+
+/// returns the (x, y) coordinates of the square with index `square`, counted from 0
+const fn coordinates(square: usize) -> (usize, usize) {
+    let per_row = MATRIX_SIZE / 2;
+    let y = square / per_row;
+    (square % per_row * 2 + (1 - y % 2), y)
+}
+
+const fn rays() -> [[Ray; 4]; SQUARES] {
+    let empty = Ray {
+        squares: [0; MATRIX_SIZE - 1],
+        length: 0,
+    };
+    let mut rays = [[empty; 4]; SQUARES];
+    let mut square = 0;
+    while square < SQUARES {
+        let mut direction = 0;
+        while direction < 4 {
+            let (dx, dy) = DIRECTIONS[direction];
+            let (x, y) = coordinates(square);
+            let (mut x, mut y) = (x as isize + dx, y as isize + dy);
+            let mut ray = empty;
+            while x >= 0 && y >= 0 && x < MATRIX_SIZE as isize && y < MATRIX_SIZE as isize {
+                ray.squares[ray.length] = (y as usize * MATRIX_SIZE / 2 + x as usize / 2) as u8;
+                ray.length += 1;
+                x += dx;
+                y += dy;
+            }
+            rays[square][direction] = ray;
+            direction += 1;
+        }
+        square += 1;
+    }
+    rays
+}
+
+const fn matrix_indexes() -> [usize; SQUARES] {
+    let mut indexes = [0; SQUARES];
+    let mut square = 0;
+    while square < SQUARES {
+        let (x, y) = coordinates(square);
+        indexes[square] = x + y * MATRIX_SIZE;
+        square += 1;
+    }
+    indexes
+}
+
+fn bit(square: usize) -> u64 {
+    1 << square
+}
+
+/// iterates over the indexes of the set bits of `mask`
+pub(crate) fn squares(mut mask: u64) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        if mask == 0 {
+            return None;
+        }
+        let square = mask.trailing_zeros() as usize;
+        mask &= mask - 1;
+        Some(square)
+    })
+}
+
+/// Move on a `BitBoard`, with squares counted from 0
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BitMove {
+    pub from: u8,
+    pub to: u8,
+    /// mask of the captured pieces
+    pub captured: u64,
+}
+
+impl BitMove {
+    /// returns `true` if `turn` goes between the same squares taking the same pieces
+    pub fn matches(&self, turn: &Move) -> bool {
+        let square = |position: Position| position.square().ok().map(|square| square - 1);
+        let captured = turn
+            .kills
+            .iter()
+            .try_fold(0, |mask, &killed| Some(mask | bit(square(killed)?)));
+        square(turn.starting_position) == Some(self.from as usize)
+            && square(turn.end_position) == Some(self.to as usize)
+            && captured == Some(self.captured)
+    }
+}
+
+/// Pieces of both sides as masks of the 50 dark squares
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BitBoard {
+    pub white: u64,
+    pub black: u64,
+    /// kings of both sides
+    pub kings: u64,
+}
+
+impl BitBoard {
+    /// returns the initial position: black on squares 1 to 20, white on 31 to 50
+    pub fn new() -> Self {
+        Self {
+            white: ((1 << 20) - 1) << 30,
+            black: (1 << 20) - 1,
+            kings: 0,
+        }
+    }
+
+    /// returns the pieces of `color`
+    pub fn pieces(&self, color: Color) -> u64 {
+        match color {
+            Color::White => self.white,
+            Color::Black => self.black,
+            Color::None => 0,
+        }
+    }
+
+    pub fn occupied(&self) -> u64 {
+        self.white | self.black
+    }
+
+    /// returns every legal move of `color`: the captures taking the most pieces if there
+    /// are any, otherwise the steps of the pawns and the slides of the kings
+    pub fn legal_moves(&self, color: Color) -> Vec<BitMove> {
+        let own = self.pieces(color);
+        let mut captures = Vec::new();
+        for from in squares(own) {
+            let capture = Capture {
+                from,
+                king: self.kings & bit(from) != 0,
+                enemies: self.pieces(color.opposite()),
+                occupied: self.occupied() & !bit(from),
+            };
+            capture.extend(from, 0, &mut captures);
+        }
+
+        if !captures.is_empty() {
+            let most = captures
+                .iter()
+                .map(|turn| turn.captured.count_ones())
+                .max()
+                .unwrap_or(0);
+            captures.retain(|turn| turn.captured.count_ones() == most);
+            captures.sort_unstable();
+            captures.dedup();
+            return captures;
+        }
+
+        let mut moves = Vec::new();
+        self.for_each_quiet_move(color, |turn| moves.push(turn));
+        moves
+    }
+
+    /// returns the number of moves `color` could make if it had no capture
+    pub fn mobility(&self, color: Color) -> usize {
+        let mut count = 0;
+        self.for_each_quiet_move(color, |_| count += 1);
+        count
+    }
+
+    /// calls `f` with every step of the pawns and slide of the kings of `color`
+    fn for_each_quiet_move<F>(&self, color: Color, mut f: F)
+    where
+        F: FnMut(BitMove),
+    {
+        let forward = if color == Color::White {
+            WHITE_FORWARD
+        } else {
+            BLACK_FORWARD
+        };
+        let empty = !self.occupied();
+        for from in squares(self.pieces(color)) {
+            let king = self.kings & bit(from) != 0;
+            for (direction, ray) in RAYS[from].iter().enumerate() {
+                if !king && !forward.contains(&direction) {
+                    continue;
+                }
+                let reach = if king { ray.length } else { ray.length.min(1) };
+                for &to in ray.squares[..reach]
+                    .iter()
+                    .take_while(|&&to| empty & bit(to as usize) != 0)
+                {
+                    f(BitMove {
+                        from: from as u8,
+                        to,
+                        captured: 0,
+                    });
+                }
+            }
+        }
+    }
+
+    /// plays `turn`, promoting a pawn which finishes it on the last row
+    pub fn apply(&mut self, turn: &BitMove) {
+        let from = bit(turn.from as usize);
+        let to = bit(turn.to as usize);
+        let white = self.white & from != 0;
+        let (own, enemies, last_row) = if white {
+            (&mut self.white, &mut self.black, WHITE_LAST_ROW)
+        } else {
+            (&mut self.black, &mut self.white, BLACK_LAST_ROW)
+        };
+        *own = *own & !from | to;
+        *enemies &= !turn.captured;
+
+        let king = self.kings & from != 0;
+        self.kings &= !from & !turn.captured;
+        if king || to & last_row != 0 {
+            self.kings |= to;
+        }
+    }
+
+    /// returns every position from which a step or a slide of `color` leads to this one,
+    /// taking back promotions too. Positions where `color` had a capture, which it would
+    /// have had to play instead, are among them.
+    pub(crate) fn quiet_predecessors(&self, color: Color) -> Vec<BitBoard> {
+        let (backward, last_row) = if color == Color::White {
+            (BLACK_FORWARD, WHITE_LAST_ROW)
+        } else {
+            (WHITE_FORWARD, BLACK_LAST_ROW)
+        };
+        let empty = !self.occupied();
+        let mut predecessors = Vec::new();
+        for to in squares(self.pieces(color)) {
+            let king = self.kings & bit(to) != 0;
+            // a king may also be a pawn crowned by the step
+            let pawn = !king || last_row & bit(to) != 0;
+            for (direction, ray) in RAYS[to].iter().enumerate() {
+                let froms = ray.squares[..ray.length]
+                    .iter()
+                    .map(|&from| from as usize)
+                    .take_while(|&from| empty & bit(from) != 0);
+                if king {
+                    for from in froms.clone() {
+                        predecessors.push(self.moved_back(color, to, from, true));
+                    }
+                }
+                if pawn && backward.contains(&direction) {
+                    if let Some(from) = froms.take(1).next() {
+                        predecessors.push(self.moved_back(color, to, from, false));
+                    }
+                }
+            }
+        }
+        predecessors
+    }
+
+    /// returns the position with the piece of `color` on `to` standing back on `from`,
+    /// as a king or as a pawn
+    fn moved_back(&self, color: Color, to: usize, from: usize, king: bool) -> BitBoard {
+        let mut previous = *self;
+        let own = if color == Color::White {
+            &mut previous.white
+        } else {
+            &mut previous.black
+        };
+        *own = *own & !bit(to) | bit(from);
+        previous.kings &= !bit(to);
+        if king {
+            previous.kings |= bit(from);
+        }
+        previous
+    }
+
+    /// returns the Zobrist hash of the position with `turn` to move,
+    /// the same `Board::position_hash` gives for the same position
+    pub fn hash(&self, turn: Color) -> u64 {
+        let mut hash = zobrist::side(turn);
+        for square in squares(self.occupied()) {
+            let piece = self.piece(square);
+            hash ^= zobrist::piece(MATRIX_INDEXES[square], piece);
+        }
+        hash
+    }
+
+    /// returns the piece standing on the square with index `square`, counted from 0
+    fn piece(&self, square: usize) -> Piece {
+        let king = self.kings & bit(square) != 0;
+        match (
+            self.white & bit(square) != 0,
+            self.black & bit(square) != 0,
+            king,
+        ) {
+            (true, _, false) => Piece::WhitePawn,
+            (true, _, true) => Piece::WhiteQueen,
+            (_, true, false) => Piece::BlackPawn,
+            (_, true, true) => Piece::BlackQueen,
+            _ => Piece::Empty,
+        }
+    }
+}
+
+impl Default for BitBoard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<&Board> for BitBoard {
+    fn from(board: &Board) -> Self {
+        let mut bits = BitBoard {
+            white: 0,
+            black: 0,
+            kings: 0,
+        };
+        for square in 0..SQUARES {
+            let position = Position::from_square(square + 1).expect("every square has a cell");
+            let piece = board[position];
+            if piece.is_white() {
+                bits.white |= bit(square);
+            } else if piece.is_black() {
+                bits.black |= bit(square);
+            }
+            if piece.is_queen() {
+                bits.kings |= bit(square);
+            }
+        }
+        bits
+    }
+}
+
+impl From<&BitBoard> for Board {
+    fn from(bits: &BitBoard) -> Self {
+        Board::from_fn(|x, y| match Position((x, y)).square() {
+            Ok(square) => bits.piece(square - 1),
+            Err(_) => Piece::Empty,
+        })
+    }
+}
+
+/// Capture being followed from square to square
+struct Capture {
+    from: usize,
+    king: bool,
+    enemies: u64,
+    /// every piece but the capturing one, the captured pieces staying until the end
+    occupied: u64,
+}
+
+impl Capture {
+    /// looks for every way of going on capturing from `at` with `captured` already taken,
+    /// pushing the captures that can not go any further
+    fn extend(&self, at: usize, captured: u64, captures: &mut Vec<BitMove>) {
+        let mut extended = false;
+        for ray in &RAYS[at] {
+            let ray = &ray.squares[..ray.length];
+            let occupied = |&square: &u8| self.occupied & bit(square as usize) != 0;
+            let distance = if self.king {
+                match ray.iter().position(occupied) {
+                    Some(distance) => distance,
+                    None => continue,
+                }
+            } else {
+                0
+            };
+            let victim = match ray.get(distance) {
+                Some(&victim) => victim as usize,
+                None => continue,
+            };
+            if self.enemies & bit(victim) == 0 || captured & bit(victim) != 0 {
+                continue;
+            }
+            let reach = if self.king { ray.len() } else { 1 };
+            let landings = ray[distance + 1..]
+                .iter()
+                .take(reach)
+                .take_while(|square| !occupied(square));
+            for &landing in landings {
+                extended = true;
+                self.extend(landing as usize, captured | bit(victim), captures);
+            }
+        }
+
+        if !extended && captured != 0 {
+            captures.push(BitMove {
+                from: self.from as u8,
+                to: at as u8,
+                captured,
+            });
+        }
+    }
+}
+
+/// checks both boards have the same legal moves for `color`
+#[cfg(test)]
+fn assert_same_moves(board: &Board, color: Color) {
+    let bits = BitBoard::from(board);
+    let matrix = board.legal_moves(color);
+    let moves = bits.legal_moves(color);
+    assert_eq!(moves.len(), matrix.len(), "{}", board.to_fen(color));
+    for turn in &matrix {
+        assert!(
+            moves.iter().any(|bit_move| bit_move.matches(turn)),
+            "{} missing in {}",
+            turn.notation(board.size()),
+            board.to_fen(color)
+        );
+    }
+}
+
+#[test]
+fn test_conversions() {
+    let board = Board::new();
+    let bits = BitBoard::from(&board);
+    assert_eq!(bits, BitBoard::new());
+    assert_eq!(Board::from(&bits), board);
+
+    let (board, _) = Board::from_fen("W:WK10,K15,18,24,27-28:B12,16,20,K22,K25,K29").unwrap();
+    let bits = BitBoard::from(&board);
+    assert_eq!(bits.kings.count_ones(), 5);
+    assert_eq!(Board::from(&bits), board);
+    for &color in &[Color::White, Color::Black] {
+        assert_eq!(bits.hash(color), board.position_hash(color));
+    }
+}
+
+#[test]
+fn test_rays() {
+    let ray = RAYS[45][1];
+    assert_eq!(ray.length, 9);
+    assert_eq!(ray.squares[..3], [40, 36, 31]);
+    assert_eq!(RAYS[0][0].length, 0);
+    assert_eq!(RAYS[0][2].squares[0], 5);
+    assert_eq!(MATRIX_INDEXES[0], 1);
+    assert_eq!(MATRIX_INDEXES[49], 98);
+}
+
+#[test]
+fn test_same_moves_as_the_matrix() {
+    let positions = [
+        "W:W31-50:B1-20",
+        "W:WK10,K15,18,24,27-28:B12,16,20,K22,K25,K29",
+        "B:WK10,K15,18,24,27-28:B12,16,20,K22,K25,K29",
+        "W:W28,K46:B7,9,17,19,22,23,32,33",
+        "B:W6,15,24,27,33,37:B1,K2,13",
+        "W:WK1:B7,12,18,23,29,34,40",
+        "W:W8,13:B2,3,19,30",
+    ];
+    for fen in &positions {
+        let (board, color) = Board::from_fen(fen).unwrap();
+        assert_same_moves(&board, color);
+    }
+}
+
+#[test]
+fn test_same_games_as_the_matrix() {
+    for game in 0..6 {
+        let mut board = Board::new();
+        let mut bits = BitBoard::new();
+        let mut color = Color::White;
+        for ply in 0..120 {
+            assert_same_moves(&board, color);
+            let moves = board.legal_moves(color);
+            if moves.is_empty() {
+                break;
+            }
+            let turn = &moves[(ply * 7 + game * 13) % moves.len()];
+            let bit_move = bits
+                .legal_moves(color)
+                .into_iter()
+                .find(|bit_move| bit_move.matches(turn))
+                .unwrap();
+            board.apply(turn);
+            bits.apply(&bit_move);
+            assert_eq!(Board::from(&bits), board);
+            color = color.opposite();
+        }
+    }
+}
+
+#[test]
+fn test_quiet_predecessors() {
+    let positions = [
+        "W:W31-50:B1-20",
+        "W:WK10,K15,18,24,27-28:B12,16,20,K22,K25,K29",
+        "B:WK10,K15,18,24,27-28:B12,16,20,K22,K25,K29",
+        "W:W6,8,K46:B45,K5",
+        "B:W6:B44,K3",
+    ];
+    for fen in &positions {
+        let (board, color) = Board::from_fen(fen).unwrap();
+        let bits = BitBoard::from(&board);
+        for turn in bits.legal_moves(color) {
+            if turn.captured != 0 {
+                continue;
+            }
+            let mut next = bits;
+            next.apply(&turn);
+            let predecessors = next.quiet_predecessors(color);
+            assert!(predecessors.contains(&bits), "{} {:?}", fen, turn);
+        }
+        // taking a quiet move back and playing it again leads to the same position
+        for previous in bits.quiet_predecessors(color.opposite()) {
+            let quiet = previous.legal_moves(color.opposite());
+            if quiet.iter().any(|turn| turn.captured != 0) {
+                continue;
+            }
+            assert!(quiet.iter().any(|turn| {
+                let mut next = previous;
+                next.apply(turn);
+                next == bits
+            }));
+        }
+    }
+}
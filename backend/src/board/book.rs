@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use rand::Rng;
+
+use crate::board::piece::Color;
+use crate::board::turn::Move;
+use crate::board::Board;
+use crate::game::Game;
+
+/// Moves played in known openings, found by the hash of the position they are played in,
+/// which tells apart the game types played from the same position.
+///
+/// Books are stored as text, one move per line with the hash of the position in hex,
+/// the move in draughts notation, squares numbered on the board of the position,
+/// and its weight, lines starting with `#` being comments:
+///
+/// ```text
+/// # hash move weight
+/// 3a6f0d2c19e5b804 32-28 12
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OpeningBook {
+    entries: HashMap<u64, Vec<(String, u32)>>,
+}
+
+impl OpeningBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// reads a book from a file
+    pub fn load<P>(path: P) -> Result<Self, BookError>
+    where
+        P: AsRef<Path>,
+    {
+        let text = fs::read_to_string(path).map_err(BookError::Io)?;
+        OpeningBook::parse(&text)
+    }
+
+    /// reads a book from its text
+    pub fn parse(text: &str) -> Result<Self, BookError> {
+        let mut book = OpeningBook::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let syntax = || BookError::Syntax {
+                line: number + 1,
+                text: line.to_string(),
+            };
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (hash, notation, weight) = match fields.as_slice() {
+                [hash, notation, weight] => (hash, notation, weight),
+                _ => return Err(syntax()),
+            };
+            let hash = u64::from_str_radix(hash, 16).map_err(|_| syntax())?;
+            let weight = weight.parse::<u32>().map_err(|_| syntax())?;
+            book.add(hash, notation, weight);
+        }
+        Ok(book)
+    }
+
+    /// writes the book as text, positions sorted by hash
+    pub fn to_text(&self) -> String {
+        let mut hashes: Vec<&u64> = self.entries.keys().collect();
+        hashes.sort();
+
+        let mut text = String::from("# hash move weight\n");
+        for hash in hashes {
+            for (notation, weight) in &self.entries[hash] {
+                text.push_str(&format!("{:016x} {} {}\n", hash, notation, weight));
+            }
+        }
+        text
+    }
+
+    /// adds `weight` to the move written as `notation` in the position of given `hash`
+    pub fn add(&mut self, hash: u64, notation: &str, weight: u32) {
+        let moves = self.entries.entry(hash).or_default();
+        match moves.iter_mut().find(|(known, _)| known == notation) {
+            Some((_, total)) => *total += weight,
+            None => moves.push((notation.to_string(), weight)),
+        }
+    }
+
+    /// adds the first `plies` moves of `game`, each counting once
+    pub fn add_game(&mut self, game: &Game, plies: usize) {
+        let (board, color) = game.start();
+        let mut board = board.clone();
+        let mut color = color;
+        for turn in game.moves().iter().take(plies) {
+            self.add(board.position_hash(color), &turn.notation(board.size()), 1);
+            board.apply(turn);
+            color = color.opposite();
+        }
+    }
+
+    /// returns the number of positions in the book
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// returns the legal moves of `color` the book knows on `board`, with their weights
+    pub fn lookup(&self, board: &Board, color: Color) -> Vec<(Move, u32)> {
+        let moves = match self.entries.get(&board.position_hash(color)) {
+            Some(moves) => moves,
+            None => return Vec::new(),
+        };
+        let legal = board.legal_moves(color);
+        moves
+            .iter()
+            .filter(|(_, weight)| *weight > 0)
+            .filter_map(|(notation, weight)| {
+                legal
+                    .iter()
+                    .find(|turn| turn.notation(board.size()) == *notation)
+                    .map(|turn| (turn.clone(), *weight))
+            })
+            .collect()
+    }
+
+    /// picks one of the book moves of `color` on `board` at random, using `rng`,
+    /// as often as its weight says
+    pub fn pick<R>(&self, board: &Board, color: Color, rng: &mut R) -> Option<Move>
+    where
+        R: Rng,
+    {
+        let moves = self.lookup(board, color);
+        let total: u32 = moves.iter().map(|(_, weight)| weight).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut chosen = rng.gen_range(0, total);
+        for (turn, weight) in moves {
+            if chosen < weight {
+                return Some(turn);
+            }
+            chosen -= weight;
+        }
+        None
+    }
+}
+
+/// Reasons a book can not be read
+#[derive(Debug)]
+pub enum BookError {
+    Io(io::Error),
+    /// the line is not a hash, a move and a weight
+    Syntax {
+        line: usize,
+        text: String,
+    },
+}
+
+impl fmt::Display for BookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BookError::Io(error) => write!(f, "can not read the book: {}", error),
+            BookError::Syntax { line, text } => write!(f, "invalid line {}: `{}`", line, text),
+        }
+    }
+}
+
+impl Error for BookError {}
+
+#[cfg(test)]
+use crate::board::position::Position;
+#[cfg(test)]
+use crate::board::rules::GameType;
+#[cfg(test)]
+use rand::{rngs::StdRng, SeedableRng};
+
+/// returns the move of `color` on `board` between the given squares
+#[cfg(test)]
+fn book_move(board: &Board, color: Color, from: usize, to: usize) -> Move {
+    board
+        .legal_moves(color)
+        .into_iter()
+        .find(|turn| {
+            turn.starting_position == Position::from_square(from).unwrap()
+                && turn.end_position == Position::from_square(to).unwrap()
+        })
+        .unwrap()
+}
+
+#[test]
+fn test_add_game() {
+    let mut game = Game::new();
+    let first = book_move(game.board(), Color::White, 32, 28);
+    game.play(&first).unwrap();
+    let second = book_move(game.board(), Color::Black, 19, 23);
+    game.play(&second).unwrap();
+
+    let mut book = OpeningBook::new();
+    book.add_game(&game, 10);
+    book.add_game(&game, 1);
+    assert_eq!(book.len(), 2);
+
+    let board = Board::new();
+    let moves = book.lookup(&board, Color::White);
+    assert_eq!(moves.len(), 1);
+    assert!(moves[0].0.is_equivalent(&first));
+    assert_eq!(moves[0].1, 2);
+    assert!(book.lookup(&board, Color::Black).is_empty());
+
+    let mut rng = StdRng::seed_from_u64(7);
+    let picked = book.pick(&board, Color::White, &mut rng).unwrap();
+    assert!(picked.is_equivalent(&first));
+}
+
+#[test]
+fn test_game_types() {
+    let mut game = Game::new();
+    let first = book_move(game.board(), Color::White, 32, 28);
+    game.play(&first).unwrap();
+    let mut book = OpeningBook::new();
+    book.add_game(&game, 10);
+    assert_eq!(book.lookup(&Board::new(), Color::White).len(), 1);
+
+    let frisian = Board::initial(GameType::Frisian);
+    assert!(frisian
+        .legal_moves(Color::White)
+        .iter()
+        .any(|turn| turn.is_equivalent(&first)));
+    assert!(book.lookup(&frisian, Color::White).is_empty());
+    let mut rng = StdRng::seed_from_u64(7);
+    assert!(book.pick(&frisian, Color::White, &mut rng).is_none());
+
+    let english = Board::initial(GameType::English);
+    let pool = Board::initial(GameType::Pool);
+    assert_ne!(
+        english.position_hash(Color::Black),
+        pool.position_hash(Color::Black)
+    );
+}
+
+#[test]
+fn test_text() {
+    let board = Board::new();
+    let hash = board.position_hash(Color::White);
+    let text = format!(
+        "# hash move weight\n{:016x} 32-28 3\n{:016x} 33-28 1\n",
+        hash, hash
+    );
+    let book = OpeningBook::parse(&text).unwrap();
+    assert_eq!(book.to_text(), text);
+    assert_eq!(OpeningBook::parse(&book.to_text()).unwrap(), book);
+
+    let mut rng = StdRng::seed_from_u64(7);
+    let picked: Vec<String> = (0..40)
+        .map(|_| {
+            book.pick(&board, Color::White, &mut rng)
+                .unwrap()
+                .notation(10)
+        })
+        .collect();
+    assert!(picked.iter().any(|turn| turn == "32-28"));
+    assert!(picked.iter().any(|turn| turn == "33-28"));
+}
+
+#[test]
+fn test_errors() {
+    assert!(matches!(
+        OpeningBook::parse("\nzz 32-28 1"),
+        Err(BookError::Syntax { line: 2, .. })
+    ));
+    assert!(matches!(
+        OpeningBook::parse("12ab 32-28"),
+        Err(BookError::Syntax { line: 1, .. })
+    ));
+    assert!(matches!(
+        OpeningBook::load("no/such/book.txt"),
+        Err(BookError::Io(_))
+    ));
+}
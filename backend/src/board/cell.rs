@@ -56,11 +56,13 @@ impl Cell for (usize, &Piece) {
     /// returns `PieceVariant` of the piece
     ///
     /// ```
-    /// assert_eq!(Piece::Empty.piece_type(), PieceType::None);
-    /// assert_eq!(Piece::WhitePawn.piece_type(), PieceType::Pawn);
-    /// assert_eq!(Piece::BlackPawn.piece_type(), PieceType::Pawn);
-    /// assert_eq!(Piece::BlackQueen.piece_type(), PieceType::Queen);
-    /// assert_eq!(Piece::WhiteQueen.piece_type(), PieceType::Queen);
+    /// # use backend::board::cell::Cell;
+    /// # use backend::board::piece::{Piece, Variant};
+    /// assert_eq!((0, &Piece::Empty).variant(), Variant::None);
+    /// assert_eq!((0, &Piece::WhitePawn).variant(), Variant::Pawn);
+    /// assert_eq!((0, &Piece::BlackPawn).variant(), Variant::Pawn);
+    /// assert_eq!((0, &Piece::BlackQueen).variant(), Variant::Queen);
+    /// assert_eq!((0, &Piece::WhiteQueen).variant(), Variant::Queen);
     /// ```
     fn variant(&self) -> Self::Variant {
         if self.1.is_pawn() {
@@ -125,11 +127,13 @@ impl Cell for (usize, Piece) {
     /// returns `PieceVariant` of the piece
     ///
     /// ```
-    /// assert_eq!(Piece::Empty.piece_type(), PieceType::None);
-    /// assert_eq!(Piece::WhitePawn.piece_type(), PieceType::Pawn);
-    /// assert_eq!(Piece::BlackPawn.piece_type(), PieceType::Pawn);
-    /// assert_eq!(Piece::BlackQueen.piece_type(), PieceType::Queen);
-    /// assert_eq!(Piece::WhiteQueen.piece_type(), PieceType::Queen);
+    /// # use backend::board::cell::Cell;
+    /// # use backend::board::piece::{Piece, Variant};
+    /// assert_eq!((0, Piece::Empty).variant(), Variant::None);
+    /// assert_eq!((0, Piece::WhitePawn).variant(), Variant::Pawn);
+    /// assert_eq!((0, Piece::BlackPawn).variant(), Variant::Pawn);
+    /// assert_eq!((0, Piece::BlackQueen).variant(), Variant::Queen);
+    /// assert_eq!((0, Piece::WhiteQueen).variant(), Variant::Queen);
     /// ```
     fn variant(&self) -> Self::Variant {
         if self.1.is_pawn() {
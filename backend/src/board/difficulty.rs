@@ -0,0 +1,298 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::board::bitboard::BitBoard;
+use crate::board::book::OpeningBook;
+use crate::board::evaluation::Weights;
+use crate::board::piece::Color;
+use crate::board::rules::GameType;
+use crate::board::search::{score_moves, SearchSettings, Threads, TimeControl};
+use crate::board::tablebase::Tablebase;
+use crate::board::turn::Move;
+use crate::board::Board;
+
+/// Strength of the engine, serialized as `"easy"`, `"medium"`, `"hard"` or `"expert"`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+/// Limits put on the search to play at a difficulty
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    /// how many plies the engine looks ahead
+    pub depth: usize,
+    /// time the engine may think for, `None` lets it reach `depth`
+    pub time: Option<TimeControl>,
+    /// entries of the transposition table built for each move, enough for the nodes
+    /// searched at `depth` without making every shallow search clear megabytes
+    pub table_size: usize,
+    /// moves scoring at most this much below the best one are played as often as the best one
+    pub tolerance: i32,
+    /// chance of searching without playing out the captures left at the horizon,
+    /// missing the shots that end beyond the depth
+    pub oversight: f64,
+}
+
+impl Difficulty {
+    pub fn profile(self) -> Profile {
+        match self {
+            Difficulty::Easy => Profile {
+                depth: 2,
+                time: None,
+                table_size: 1 << 10,
+                tolerance: 120,
+                oversight: 0.3,
+            },
+            Difficulty::Medium => Profile {
+                depth: 4,
+                time: None,
+                table_size: 1 << 14,
+                tolerance: 40,
+                oversight: 0.1,
+            },
+            Difficulty::Hard => Profile {
+                depth: 6,
+                time: None,
+                table_size: 1 << 16,
+                tolerance: 10,
+                oversight: 0.0,
+            },
+            Difficulty::Expert => Profile {
+                depth: 20,
+                time: Some(TimeControl::MoveTime(Duration::from_secs(2))),
+                table_size: 1 << 20,
+                tolerance: 0,
+                oversight: 0.0,
+            },
+        }
+    }
+}
+
+/// What the engine knows besides the search, whatever its difficulty
+#[derive(Debug, Clone, Default)]
+pub struct EngineContext {
+    /// weights of the evaluation at the end of the search
+    pub weights: Weights,
+    /// moves played without searching while the position is in the book
+    pub book: Option<Arc<OpeningBook>>,
+    /// results probed instead of searched once few pieces are left
+    pub tablebase: Option<Arc<Tablebase>>,
+}
+
+impl Board {
+    /// returns the move the engine plays for `color` at `difficulty` with `context`.
+    /// Returns `None` if `color` has no legal moves.
+    pub fn find_move_at(
+        &self,
+        color: Color,
+        difficulty: Difficulty,
+        context: &EngineContext,
+    ) -> Option<Move> {
+        self.find_move_with_profile(
+            color,
+            &difficulty.profile(),
+            context,
+            &mut rand::thread_rng(),
+        )
+    }
+
+    /// returns a move of `color` picked at random, using `rng`, among the book moves
+    /// of `context`, else among those scoring within the tolerance of `profile`
+    /// from the best one. Without tolerance it is the best move found by the full search.
+    pub fn find_move_with_profile<R>(
+        &self,
+        color: Color,
+        profile: &Profile,
+        context: &EngineContext,
+        rng: &mut R,
+    ) -> Option<Move>
+    where
+        R: Rng,
+    {
+        if let Some(book) = &context.book {
+            if let Some(turn) = book.pick(self, color, rng) {
+                return Some(turn);
+            }
+        }
+
+        // games against the engine search side by side, each on a single thread
+        let settings = SearchSettings {
+            depth: profile.depth,
+            time: profile.time,
+            table_size: profile.table_size,
+            threads: Threads::Single,
+            tablebase: context.tablebase.clone(),
+            quiescence: !rng.gen_bool(profile.oversight),
+            ..SearchSettings::default()
+        };
+        if profile.tolerance == 0 {
+            return self
+                .find_best_move_with(color, &settings, &context.weights)
+                .map(|(turn, _)| turn);
+        }
+
+        let scores = self.score_moves(color, &settings, &context.weights);
+        let best = scores.iter().map(|&(_, score)| score).max()?;
+        let candidates: Vec<_> = scores
+            .into_iter()
+            .filter(|&(_, score)| score >= best - profile.tolerance)
+            .map(|(turn, _)| turn)
+            .collect();
+        candidates.choose(rng).cloned()
+    }
+
+    /// returns every legal move of `color` with its score, international positions
+    /// being searched on their `BitBoard`
+    fn score_moves(
+        &self,
+        color: Color,
+        settings: &SearchSettings,
+        weights: &Weights,
+    ) -> Vec<(Move, i32)> {
+        if self.game_type() != GameType::International {
+            return score_moves(self, color, settings, weights);
+        }
+        let legal = self.legal_moves(color);
+        score_moves(&BitBoard::from(self), color, settings, weights)
+            .into_iter()
+            .map(|(bit_move, score)| {
+                let turn = legal
+                    .iter()
+                    .find(|turn| bit_move.matches(turn))
+                    .expect("both boards have the same legal moves");
+                (turn.clone(), score)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+use crate::board::{board_with, piece::Piece, position::Position};
+#[cfg(test)]
+use rand::{rngs::StdRng, SeedableRng};
+
+#[test]
+fn test_profiles_get_stronger() {
+    let profiles: Vec<Profile> = [
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Expert,
+    ]
+    .iter()
+    .map(|difficulty| difficulty.profile())
+    .collect();
+    for pair in profiles.windows(2) {
+        assert!(pair[0].depth < pair[1].depth);
+        assert!(pair[0].table_size < pair[1].table_size);
+        assert!(pair[0].tolerance > pair[1].tolerance);
+        assert!(pair[0].oversight >= pair[1].oversight);
+    }
+    assert_eq!(
+        serde_json::from_str::<Difficulty>(r#""medium""#).unwrap(),
+        Difficulty::Medium
+    );
+}
+
+#[test]
+fn test_near_best_moves() {
+    let board = Board::new();
+    let profile = Profile {
+        depth: 2,
+        time: None,
+        table_size: 1 << 10,
+        tolerance: 1000,
+        oversight: 0.0,
+    };
+    let mut rng = StdRng::seed_from_u64(7);
+    let played: Vec<Move> = (0..20)
+        .map(|_| {
+            board
+                .find_move_with_profile(Color::White, &profile, &EngineContext::default(), &mut rng)
+                .unwrap()
+        })
+        .collect();
+    assert!(played.iter().any(|turn| !turn.is_equivalent(&played[0])));
+}
+
+#[test]
+fn test_small_tolerance_keeps_the_material() {
+    // stepping to 28 loses the pawn to the black one on 22
+    let board = board_with(&[
+        ((5, 6), Piece::WhitePawn),
+        ((9, 8), Piece::WhitePawn),
+        ((3, 4), Piece::BlackPawn),
+    ]);
+    let profile = Profile {
+        tolerance: 50,
+        ..Difficulty::Hard.profile()
+    };
+    let mut rng = StdRng::seed_from_u64(7);
+    for _ in 0..10 {
+        let turn = board
+            .find_move_with_profile(Color::White, &profile, &EngineContext::default(), &mut rng)
+            .unwrap();
+        assert_ne!(turn.end_position, Position((4, 5)));
+    }
+    assert_eq!(
+        board_with(&[]).find_move_at(Color::White, Difficulty::Easy, &EngineContext::default()),
+        None
+    );
+}
+
+#[test]
+fn test_oversight_misses_the_shot() {
+    // giving the pawn on 33 away on 28 wins both black pawns: 22 takes it, 39 takes back two
+    let board = board_with(&[
+        ((5, 6), Piece::WhitePawn),
+        ((6, 7), Piece::WhitePawn),
+        ((7, 8), Piece::WhitePawn),
+        ((3, 6), Piece::WhitePawn),
+        ((3, 4), Piece::BlackPawn),
+        ((5, 4), Piece::BlackPawn),
+    ]);
+    let context = EngineContext {
+        weights: Weights::material(),
+        ..EngineContext::default()
+    };
+    let shot = |profile: &Profile| {
+        let mut rng = StdRng::seed_from_u64(7);
+        let turn = board
+            .find_move_with_profile(Color::White, profile, &context, &mut rng)
+            .unwrap();
+        turn.starting_position == Position((5, 6)) && turn.end_position == Position((4, 5))
+    };
+
+    assert!(shot(&Difficulty::Hard.profile()));
+    let blind = Profile {
+        tolerance: 0,
+        oversight: 1.0,
+        ..Difficulty::Easy.profile()
+    };
+    assert!(!shot(&blind));
+    assert!(shot(&Profile {
+        oversight: 0.0,
+        ..blind
+    }));
+}
+
+#[test]
+fn test_english_board() {
+    let board = Board::initial(GameType::English);
+    let legal = board.legal_moves(Color::Black);
+    for &difficulty in &[Difficulty::Easy, Difficulty::Medium] {
+        let turn = board
+            .find_move_at(Color::Black, difficulty, &EngineContext::default())
+            .unwrap();
+        assert!(legal.iter().any(|other| other.is_equivalent(&turn)));
+    }
+}
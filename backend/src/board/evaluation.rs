@@ -0,0 +1,322 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::board::bitboard::{squares, BitBoard, BLACK_LAST_ROW, WHITE_LAST_ROW};
+use crate::board::cell::Cell;
+use crate::board::piece::Color;
+use crate::board::rules::GameType;
+use crate::board::{Board, MATRIX_SIZE};
+
+/// squares 22 to 24 and 27 to 29 in the middle of the board
+const CENTER: u64 = 0b111 << 21 | 0b111 << 26;
+
+/// Static evaluation of a position
+pub trait Evaluator<P>: Sync {
+    /// returns the score of `position` seen from the `color` side, `color` being to move
+    fn evaluate(&self, position: &P, color: Color) -> i32;
+}
+
+/// Evaluation summing weighted features of both sides, each feature counted
+/// for the side to move and subtracted for the opponent.
+///
+/// The weights can be loaded from a JSON or TOML file with `Weights::load`,
+/// missing ones keeping their default value:
+///
+/// ```toml
+/// pawn = 100
+/// king = 320
+/// mobility = 2
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Weights {
+    /// value of a pawn
+    pub pawn: i32,
+    /// value of a king
+    pub king: i32,
+    /// bonus of a pawn for every row it advanced from its own side
+    pub advancement: i32,
+    /// bonus of a piece standing on one of the six center squares
+    pub center: i32,
+    /// bonus of a pawn still guarding the own back row, keeping the opponent from promoting
+    pub back_rank: i32,
+    /// bonus of having the move
+    pub tempo: i32,
+    /// bonus of each move the side could make if it had no capture
+    pub mobility: i32,
+}
+
+impl Weights {
+    /// returns weights counting only the material
+    pub fn material() -> Self {
+        Self {
+            advancement: 0,
+            center: 0,
+            back_rank: 0,
+            tempo: 0,
+            mobility: 0,
+            ..Weights::default()
+        }
+    }
+
+    /// reads the weights from a JSON file, or a TOML one when the extension is `.toml`
+    pub fn load<P>(path: P) -> Result<Self, WeightsError>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(WeightsError::Io)?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => Weights::from_toml(&text),
+            _ => Weights::from_json(&text),
+        }
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, WeightsError> {
+        serde_json::from_str(text).map_err(|error| WeightsError::Parse(error.to_string()))
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, WeightsError> {
+        toml::from_str(text).map_err(|error| WeightsError::Parse(error.to_string()))
+    }
+
+    /// returns the weighted features of the `color` pieces
+    fn side(&self, bits: &BitBoard, color: Color) -> i32 {
+        let pieces = bits.pieces(color);
+        let kings = pieces & bits.kings;
+        let pawns = pieces & !bits.kings;
+        let back_row = if color == Color::White {
+            BLACK_LAST_ROW
+        } else {
+            WHITE_LAST_ROW
+        };
+
+        let per_row = MATRIX_SIZE / 2;
+        let advancement: i32 = squares(pawns)
+            .map(|square| {
+                let row = square / per_row;
+                let rows = if color == Color::White {
+                    MATRIX_SIZE - 1 - row
+                } else {
+                    row
+                };
+                rows as i32
+            })
+            .sum();
+
+        let count = |mask: u64| mask.count_ones() as i32;
+        self.pawn * count(pawns)
+            + self.king * count(kings)
+            + self.advancement * advancement
+            + self.center * count(pieces & CENTER)
+            + self.back_rank * count(pawns & back_row)
+            + self.mobility * bits.mobility(color) as i32
+    }
+
+    /// returns the weighted features of the `color` pieces on a board of any size
+    fn cells(&self, board: &Board, color: Color) -> i32 {
+        let size = board.size();
+        let center_rows = size / 2 - 1..=size / 2;
+        let center_columns = 2..size - 2;
+
+        let pieces: i32 = board
+            .positions_of(color)
+            .into_iter()
+            .map(|position| {
+                let piece = board[position];
+                let (x, y) = position.inner();
+                let center = if center_rows.contains(&y) && center_columns.contains(&x) {
+                    self.center
+                } else {
+                    0
+                };
+                if piece.is_queen() {
+                    return self.king + center;
+                }
+                let rows = if color == Color::White {
+                    size - 1 - y
+                } else {
+                    y
+                };
+                let back_rank = if rows == 0 { self.back_rank } else { 0 };
+                self.pawn + self.advancement * rows as i32 + center + back_rank
+            })
+            .sum();
+        pieces + self.mobility * board.mobility(color) as i32
+    }
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Self {
+            pawn: 100,
+            king: 300,
+            advancement: 2,
+            center: 4,
+            back_rank: 5,
+            tempo: 3,
+            mobility: 1,
+        }
+    }
+}
+
+impl Evaluator<BitBoard> for Weights {
+    fn evaluate(&self, bits: &BitBoard, color: Color) -> i32 {
+        self.side(bits, color) - self.side(bits, color.opposite()) + self.tempo
+    }
+}
+
+/// International boards are scored on their `BitBoard`, other ones cell by cell
+/// with the same features, the center being the middle of the two middle rows
+impl Evaluator<Board> for Weights {
+    fn evaluate(&self, board: &Board, color: Color) -> i32 {
+        if board.game_type() == GameType::International {
+            return self.evaluate(&BitBoard::from(board), color);
+        }
+        self.cells(board, color) - self.cells(board, color.opposite()) + self.tempo
+    }
+}
+
+/// Reasons the weights can not be loaded
+#[derive(Debug)]
+pub enum WeightsError {
+    Io(io::Error),
+    /// the file is not valid JSON or TOML, or names an unknown weight
+    Parse(String),
+}
+
+impl fmt::Display for WeightsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WeightsError::Io(error) => write!(f, "can not read the weights: {}", error),
+            WeightsError::Parse(error) => write!(f, "invalid weights: {}", error),
+        }
+    }
+}
+
+impl Error for WeightsError {}
+
+#[cfg(test)]
+use crate::board::{board_with, piece::Piece};
+
+#[test]
+fn test_material() {
+    let weights = Weights::material();
+    assert_eq!(weights.evaluate(&Board::new(), Color::White), 0);
+
+    let board = board_with(&[((1, 2), Piece::WhiteQueen), ((3, 4), Piece::BlackPawn)]);
+    assert_eq!(weights.evaluate(&board, Color::White), 300 - 100);
+    assert_eq!(weights.evaluate(&board, Color::Black), 100 - 300);
+}
+
+#[test]
+fn test_features() {
+    let only = |weights: Weights| Weights {
+        pawn: 0,
+        king: 0,
+        tempo: 0,
+        ..weights
+    };
+    let board = board_with(&[
+        ((0, 9), Piece::WhitePawn),
+        ((4, 5), Piece::WhitePawn),
+        ((3, 2), Piece::BlackPawn),
+    ]);
+
+    let advancement = only(Weights {
+        advancement: 1,
+        ..Weights::material()
+    });
+    assert_eq!(advancement.evaluate(&board, Color::White), 4 - 2);
+
+    let center = only(Weights {
+        center: 1,
+        ..Weights::material()
+    });
+    assert_eq!(center.evaluate(&board, Color::White), 1);
+
+    let back_rank = only(Weights {
+        back_rank: 1,
+        ..Weights::material()
+    });
+    assert_eq!(back_rank.evaluate(&board, Color::White), 1);
+
+    let mobility = only(Weights {
+        mobility: 1,
+        ..Weights::material()
+    });
+    assert_eq!(mobility.evaluate(&board, Color::White), 3 - 2);
+
+    let tempo = Weights {
+        tempo: 1,
+        ..only(Weights::material())
+    };
+    assert_eq!(tempo.evaluate(&board, Color::White), 1);
+    assert_eq!(tempo.evaluate(&board, Color::Black), 1);
+
+    assert_eq!(
+        Weights::default().evaluate(&Board::new(), Color::White),
+        Weights::default().tempo
+    );
+}
+
+#[test]
+fn test_other_boards() {
+    let board = Board::initial(GameType::English);
+    assert_eq!(
+        Weights::default().evaluate(&board, Color::Black),
+        Weights::default().tempo
+    );
+
+    // the white pawn on 18 stands in the center, three rows away from its own side
+    let (board, _) = Board::from_fen_for("W:W18,K32:B5", GameType::English).unwrap();
+    let features = |weights: Weights| Weights {
+        pawn: 0,
+        king: 0,
+        tempo: 0,
+        ..weights
+    };
+    let material = Weights::material();
+    assert_eq!(material.evaluate(&board, Color::White), 400 - 100);
+    let advancement = features(Weights {
+        advancement: 1,
+        ..Weights::material()
+    });
+    assert_eq!(advancement.evaluate(&board, Color::White), 3 - 1);
+    let center = features(Weights {
+        center: 1,
+        ..Weights::material()
+    });
+    assert_eq!(center.evaluate(&board, Color::White), 1);
+    let mobility = features(Weights {
+        mobility: 1,
+        ..Weights::material()
+    });
+    assert_eq!(mobility.evaluate(&board, Color::White), 2 + 2 - 1);
+}
+
+#[test]
+fn test_parse() {
+    let weights = Weights::from_json(r#"{"pawn": 90, "mobility": 5}"#).unwrap();
+    assert_eq!(weights.pawn, 90);
+    assert_eq!(weights.mobility, 5);
+    assert_eq!(weights.king, Weights::default().king);
+
+    let weights = Weights::from_toml("king = 350\ncenter = 0\n").unwrap();
+    assert_eq!(weights.king, 350);
+    assert_eq!(weights.center, 0);
+
+    assert!(matches!(
+        Weights::from_json(r#"{"queen": 300}"#),
+        Err(WeightsError::Parse(_))
+    ));
+    assert!(matches!(
+        Weights::load("no/such/weights.json"),
+        Err(WeightsError::Io(_))
+    ));
+}
@@ -0,0 +1,249 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::board::cell::Cell;
+use crate::board::piece::{Color, Piece};
+use crate::board::position::Position;
+use crate::board::rules::GameType;
+use crate::board::Board;
+
+/// Reasons a FEN string can not be read
+#[derive(Debug, Clone, PartialEq)]
+pub enum FenError {
+    /// the side to move is neither `W` nor `B`
+    InvalidTurn(String),
+    /// a piece list does not start with `W` or `B`
+    InvalidSide(String),
+    /// a square is not the number of a dark square of the board, or a range is reversed
+    InvalidSquare(String),
+    /// the same square is given more than once
+    DuplicateSquare(usize),
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FenError::InvalidTurn(turn) => write!(f, "invalid side to move `{}`", turn),
+            FenError::InvalidSide(side) => write!(f, "invalid piece list `{}`", side),
+            FenError::InvalidSquare(square) => write!(f, "invalid square `{}`", square),
+            FenError::DuplicateSquare(square) => write!(f, "square {} given twice", square),
+        }
+    }
+}
+
+impl Error for FenError {}
+
+impl Board {
+    /// reads a position in the draughts FEN format, e.g. `W:W31-50:B1-20`,
+    /// kings being prefixed with `K` like in `B:WK45,31:BK3`.
+    /// Returns the international board together with the side to move.
+    pub fn from_fen(fen: &str) -> Result<(Self, Color), FenError> {
+        Board::from_fen_for(fen, GameType::International)
+    }
+
+    /// reads a position of `game_type` in the draughts FEN format,
+    /// squares being numbered on its board
+    pub fn from_fen_for(fen: &str, game_type: GameType) -> Result<(Self, Color), FenError> {
+        let fen = fen.trim().trim_matches('"').trim_end_matches('.');
+        let mut parts = fen.split(':');
+
+        let turn = match parts.next().map(str::trim) {
+            Some("W") => Color::White,
+            Some("B") => Color::Black,
+            other => return Err(FenError::InvalidTurn(other.unwrap_or("").to_string())),
+        };
+
+        let mut board = Board::from_fn_for(game_type, |_, _| Piece::Empty);
+        for part in parts {
+            let part = part.trim();
+            let (pawn, queen) = match part.chars().next() {
+                Some('W') => (Piece::WhitePawn, Piece::WhiteQueen),
+                Some('B') => (Piece::BlackPawn, Piece::BlackQueen),
+                _ => return Err(FenError::InvalidSide(part.to_string())),
+            };
+
+            for item in part[1..]
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+            {
+                let (piece, squares) = match item.strip_prefix('K') {
+                    Some(squares) => (queen, squares),
+                    None => (pawn, item),
+                };
+                for square in parse_squares(squares, board.size())? {
+                    let position = Position::from_square_on(square, board.size())
+                        .map_err(|_| FenError::InvalidSquare(item.to_string()))?;
+                    if !board[position].is_empty() {
+                        return Err(FenError::DuplicateSquare(square));
+                    }
+                    board.set(position, piece);
+                }
+            }
+        }
+
+        Ok((board, turn))
+    }
+
+    /// writes the position in the draughts FEN format, with `turn` being the side to move
+    pub fn to_fen(&self, turn: Color) -> String {
+        let turn = if turn == Color::Black { "B" } else { "W" };
+        format!(
+            "{}:W{}:B{}",
+            turn,
+            self.fen_pieces(Color::White),
+            self.fen_pieces(Color::Black)
+        )
+    }
+
+    /// lists squares of the `color` pieces, joining runs of pawns into ranges
+    fn fen_pieces(&self, color: Color) -> String {
+        let mut items = Vec::new();
+        let mut run: Option<(usize, usize)> = None;
+
+        let size = self.size();
+        for square in 1..=size * size / 2 {
+            let position =
+                Position::from_square_on(square, size).expect("every square has a position");
+            let piece = self[position];
+            let own = piece.color() == color;
+            if own && piece.is_pawn() {
+                run = match run {
+                    Some((first, last)) if last + 1 == square => Some((first, square)),
+                    _ => {
+                        items.extend(run.map(format_run));
+                        Some((square, square))
+                    }
+                };
+            } else if own && piece.is_queen() {
+                items.extend(run.take().map(format_run));
+                items.push(format!("K{}", square));
+            }
+        }
+        items.extend(run.map(format_run));
+
+        items.join(",")
+    }
+}
+
+fn format_run((first, last): (usize, usize)) -> String {
+    if first == last {
+        first.to_string()
+    } else {
+        format!("{}-{}", first, last)
+    }
+}
+
+/// reads a single square like `31` or a range like `31-50` of a board of `size` cells a side
+fn parse_squares(squares: &str, size: usize) -> Result<Vec<usize>, FenError> {
+    let invalid = || FenError::InvalidSquare(squares.to_string());
+    let number = |number: &str| match number.trim().parse::<usize>() {
+        Ok(square) if (1..=size * size / 2).contains(&square) => Ok(square),
+        _ => Err(invalid()),
+    };
+
+    match squares.find('-') {
+        Some(dash) => {
+            let first = number(&squares[..dash])?;
+            let last = number(&squares[dash + 1..])?;
+            if first > last {
+                return Err(invalid());
+            }
+            Ok((first..=last).collect())
+        }
+        None => Ok(vec![number(squares)?]),
+    }
+}
+
+#[test]
+fn test_initial_position() {
+    let (board, turn) = Board::from_fen("W:W31-50:B1-20").unwrap();
+    assert_eq!(board, Board::new());
+    assert_eq!(turn, Color::White);
+    assert_eq!(Board::new().to_fen(Color::White), "W:W31-50:B1-20");
+}
+
+#[test]
+fn test_kings_and_single_squares() {
+    let fen = "B:W18,24,27,28,K10,K15:B12,16,20,K22,K25,K29";
+    let (board, turn) = Board::from_fen(fen).unwrap();
+    assert_eq!(turn, Color::Black);
+    assert_eq!(board[Position::from_square(10).unwrap()], Piece::WhiteQueen);
+    assert_eq!(board[Position::from_square(18).unwrap()], Piece::WhitePawn);
+    assert_eq!(board[Position::from_square(22).unwrap()], Piece::BlackQueen);
+    assert_eq!(board[Position::from_square(12).unwrap()], Piece::BlackPawn);
+    assert_eq!(board.iter().filter(|piece| !piece.is_empty()).count(), 12);
+
+    assert_eq!(
+        board.to_fen(turn),
+        "B:WK10,K15,18,24,27-28:B12,16,20,K22,K25,K29"
+    );
+}
+
+#[test]
+fn test_round_trip() {
+    let fen = "W:WK4,31-33,K46:BK5,19-20,22";
+    let (board, turn) = Board::from_fen(fen).unwrap();
+    assert_eq!(board.to_fen(turn), fen);
+    assert_eq!(Board::from_fen(&board.to_fen(turn)).unwrap(), (board, turn));
+}
+
+#[test]
+fn test_pdn_decorations() {
+    let (board, turn) = Board::from_fen("\"W:W31-50:B1-20.\"").unwrap();
+    assert_eq!(board, Board::new());
+    assert_eq!(turn, Color::White);
+
+    let (board, _) = Board::from_fen("W:B1-20:W31-50").unwrap();
+    assert_eq!(board, Board::new());
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(
+        Board::from_fen("X:W31:B1"),
+        Err(FenError::InvalidTurn("X".to_string()))
+    );
+    assert_eq!(
+        Board::from_fen("W:Q31:B1"),
+        Err(FenError::InvalidSide("Q31".to_string()))
+    );
+    assert_eq!(
+        Board::from_fen("W:W51:B1"),
+        Err(FenError::InvalidSquare("51".to_string()))
+    );
+    assert_eq!(
+        Board::from_fen("W:W20-10:B1"),
+        Err(FenError::InvalidSquare("20-10".to_string()))
+    );
+    assert_eq!(
+        Board::from_fen("W:W1:B1"),
+        Err(FenError::DuplicateSquare(1))
+    );
+    assert_eq!(
+        Board::from_fen("W:W1-18446744073709551615:B50"),
+        Err(FenError::InvalidSquare(
+            "1-18446744073709551615".to_string()
+        ))
+    );
+    assert_eq!(
+        Board::from_fen("W:W0-4:B50"),
+        Err(FenError::InvalidSquare("0-4".to_string()))
+    );
+    assert_eq!(
+        Board::from_fen_for("W:W1-33:B50", GameType::English),
+        Err(FenError::InvalidSquare("1-33".to_string()))
+    );
+}
+
+#[test]
+fn test_english_squares() {
+    let (board, turn) = Board::from_fen_for("B:W21-32:B1-12", GameType::English).unwrap();
+    assert_eq!(board, Board::initial(GameType::English));
+    assert_eq!(turn, Color::Black);
+    assert_eq!(board.to_fen(turn), "B:W21-32:B1-12");
+    assert_eq!(
+        Board::from_fen_for("W:W33:B1", GameType::English),
+        Err(FenError::InvalidSquare("33".to_string()))
+    );
+}
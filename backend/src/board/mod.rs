@@ -1,54 +1,188 @@
-mod cell;
-mod piece;
-mod position;
-mod turn;
+pub mod bitboard;
+pub mod book;
+pub mod cell;
+pub mod difficulty;
+pub mod evaluation;
+pub mod fen;
+mod moves;
+pub mod perft;
+pub mod piece;
+pub mod position;
+pub mod rules;
+pub mod search;
+pub mod status;
+pub mod tablebase;
+pub mod transposition;
+pub mod turn;
+mod zobrist;
 
 use std::fmt;
-use std::ops::{Index, IndexMut};
+use std::ops::Index;
 
 use nalgebra::base::iter::MatrixIter;
-use nalgebra::{ArrayStorage, MatrixN, U10};
+use nalgebra::{DMatrix, Dynamic, VecStorage};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::board::cell::Cell;
 use crate::board::position::Position;
-use crate::board::turn::Move;
+use crate::board::rules::{GameType, Promotion, Rules};
+use crate::board::turn::{Move, Undo};
 use piece::{Color as PieceColor, Piece};
 
+/// size of the international board, the one the `BitBoard` and the tablebases know
 const MATRIX_SIZE: usize = 10;
+/// number of the playable (dark) squares of the international board
+const SQUARES: usize = MATRIX_SIZE * MATRIX_SIZE / 2;
 
-pub type BoardIter<'a> = MatrixIter<'a, Piece, U10, U10, ArrayStorage<Piece, U10, U10>>;
+pub type BoardIter<'a> =
+    MatrixIter<'a, Piece, Dynamic, Dynamic, VecStorage<Piece, Dynamic, Dynamic>>;
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Board {
-    cells: MatrixN<Piece, U10>,
+    cells: DMatrix<Piece>,
+    /// rules the pieces move by, giving the size of `cells`
+    game_type: GameType,
+    /// Zobrist hash of the pieces, kept up to date by `set`
+    hash: u64,
 }
 
 impl Board {
+    /// returns an international board, `f` giving the piece standing on each cell
     pub fn from_fn<F>(f: F) -> Self
     where
         F: FnMut(usize, usize) -> Piece,
     {
-        let cells = MatrixN::<Piece, U10>::from_fn(f);
-        Self { cells }
+        Board::from_fn_for(GameType::International, f)
     }
 
+    /// returns a board of `game_type`, `f` giving the piece standing on each cell
+    pub fn from_fn_for<F>(game_type: GameType, f: F) -> Self
+    where
+        F: FnMut(usize, usize) -> Piece,
+    {
+        let size = game_type.rules().size;
+        let cells = DMatrix::from_fn(size, size, f);
+        let hash = cells.iter().enumerate().fold(0, |hash, (index, &piece)| {
+            hash ^ zobrist::piece(index, piece)
+        });
+        Self {
+            cells,
+            game_type,
+            hash,
+        }
+    }
+
+    /// returns the initial position of international draughts
     pub fn new() -> Self {
-        Board::from_fn(|x, y| match y {
-            0..=3 if (x + y) % 2 == 1 => Piece::BlackPawn,
-            6..=9 if (x + y) % 2 == 1 => Piece::WhitePawn,
+        Board::initial(GameType::International)
+    }
+
+    /// returns the initial position of `game_type`: pawns on the dark squares of the first
+    /// rows of each side, black ones on the side of `y = 0`
+    pub fn initial(game_type: GameType) -> Self {
+        let Rules { size, rows, .. } = game_type.rules();
+        Board::from_fn_for(game_type, |x, y| match y {
+            _ if (x + y) % 2 == 0 => Piece::Empty,
+            y if y < rows => Piece::BlackPawn,
+            y if y >= size - rows => Piece::WhitePawn,
             _ => Piece::Empty,
         })
     }
 
-    fn position_from_index(index: usize) -> Position {
-        (index / MATRIX_SIZE, index % MATRIX_SIZE).into()
+    /// returns the number of cells along each side of the board
+    pub fn size(&self) -> usize {
+        self.cells.nrows()
+    }
+
+    pub fn game_type(&self) -> GameType {
+        self.game_type
+    }
+
+    /// returns the rules of the game type of the board
+    pub fn rules(&self) -> Rules {
+        self.game_type.rules()
     }
 
     pub fn iter(&self) -> BoardIter<'_> {
         self.cells.iter()
     }
 
-    // TODO
-    // pub fn find_best_move(color: PieceColor) -> Move {}
+    /// plays `turn`: moves the piece, removes every captured one and promotes a pawn
+    /// finishing its move on the last row, or passing it during a capture where the rules
+    /// crown it right away.
+    /// Returns the `Undo` that takes the move back.
+    pub fn apply(&mut self, turn: &Move) -> Undo {
+        let piece = self[turn.starting_position];
+        let captured = turn
+            .kills
+            .iter()
+            .map(|&killed| (killed, self[killed]))
+            .collect();
+
+        for &killed in &turn.kills {
+            self.set(killed, Piece::Empty);
+        }
+        self.set(turn.starting_position, Piece::Empty);
+
+        let mut moved = piece;
+        let passed = match self.rules().promotion {
+            Promotion::AtTheEnd => &[][..],
+            Promotion::DuringCapture => &turn.path[..],
+        };
+        if passed
+            .iter()
+            .chain(Some(&turn.end_position))
+            .any(|&cell| self.is_last_row(cell, piece.color()))
+        {
+            moved.promote();
+        }
+        self.set(turn.end_position, moved);
+
+        Undo {
+            starting_position: turn.starting_position,
+            end_position: turn.end_position,
+            piece,
+            captured,
+        }
+    }
+
+    /// restores the board to the state it had before the move `undo` was returned for
+    pub fn undo(&mut self, undo: Undo) {
+        self.set(undo.end_position, Piece::Empty);
+        for (position, piece) in undo.captured {
+            self.set(position, piece);
+        }
+        self.set(undo.starting_position, undo.piece);
+    }
+
+    /// puts `piece` on `position`, replacing whatever stood there
+    pub fn set<P>(&mut self, position: P, piece: Piece)
+    where
+        P: Into<Position>,
+    {
+        let (x, y) = position.into().inner();
+        let index = x + y * self.size();
+        let cell = &mut self.cells[index];
+        self.hash ^= zobrist::piece(index, *cell) ^ zobrist::piece(index, piece);
+        *cell = piece;
+    }
+
+    /// returns the Zobrist hash of the position with `turn` to move.
+    /// It is updated along every change of the board, so it costs nothing to get.
+    pub fn position_hash(&self, turn: PieceColor) -> u64 {
+        self.hash ^ zobrist::side(turn) ^ zobrist::game_type(self.game_type)
+    }
+
+    /// returns `true` if `position` lies on the row where pawns of `color` get promoted
+    fn is_last_row(&self, position: Position, color: PieceColor) -> bool {
+        let (_, y) = position.inner();
+        match color {
+            PieceColor::White => y == 0,
+            PieceColor::Black => y == self.size() - 1,
+            PieceColor::None => false,
+        }
+    }
 }
 
 pub fn filter_by<I, C, F>(iter: I, f: F) -> Vec<C>
@@ -60,12 +194,53 @@ where
     iter.filter(f).collect()
 }
 
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl fmt::Display for Board {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.cells)
     }
 }
 
+/// The board is serialized as rows, from `y = 0` (the black side) down to the white side.
+/// Each row lists its cells from `x = 0`, every cell holding a piece code like `"WP"` or `"--"`.
+/// The rows do not tell apart the game types played on the same size,
+/// so a board is read back with `Board::deserialize_for` and the game type it is played by.
+impl Serialize for Board {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let size = self.size();
+        serializer
+            .collect_seq((0..size).map(|y| (0..size).map(|x| self[(x, y)]).collect::<Vec<_>>()))
+    }
+}
+
+impl Board {
+    /// reads a board of `game_type` serialized as rows, the rows alone not telling apart
+    /// the game types played on the same size
+    pub fn deserialize_for<'de, D>(game_type: GameType, deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let rows = Vec::<Vec<Piece>>::deserialize(deserializer)?;
+        Board::from_rows(game_type, rows).map_err(D::Error::custom)
+    }
+
+    fn from_rows(game_type: GameType, rows: Vec<Vec<Piece>>) -> Result<Self, String> {
+        let size = game_type.rules().size;
+        if rows.len() != size || rows.iter().any(|row| row.len() != size) {
+            return Err(format!("board has to have {} rows of {} cells", size, size));
+        }
+        Ok(Board::from_fn_for(game_type, |x, y| rows[y][x]))
+    }
+}
+
 impl Index<usize> for Board {
     type Output = Piece;
 
@@ -91,32 +266,27 @@ impl Index<Position> for Board {
     }
 }
 
-impl IndexMut<usize> for Board {
-    fn index_mut(&mut self, idx: usize) -> &mut Self::Output {
-        self.cells.index_mut(idx)
-    }
-}
-
-impl IndexMut<(usize, usize)> for Board {
-    fn index_mut(&mut self, pos: (usize, usize)) -> &mut Self::Output {
-        self.cells.index_mut(pos)
-    }
-}
-
-impl IndexMut<Position> for Board {
-    fn index_mut(&mut self, pos: Position) -> &mut Self::Output {
-        let pos: (usize, usize) = pos.into();
-        self.cells.index_mut(pos)
-    }
+#[cfg(test)]
+fn board_with(pieces: &[((usize, usize), Piece)]) -> Board {
+    Board::from_fn(|x, y| {
+        pieces
+            .iter()
+            .find(|(position, _)| *position == (x, y))
+            .map_or(Piece::Empty, |(_, piece)| *piece)
+    })
 }
 
 #[test]
 fn test_position_from_index() {
-    assert_eq!(Board::position_from_index(9), Position((0, 9)));
-    assert_eq!(Board::position_from_index(15), Position((1, 5)));
-    assert_eq!(Board::position_from_index(10), Position((1, 0)));
-    assert_eq!(Board::position_from_index(19), Position((1, 9)));
+    let board = Board::new();
+    for index in 0..100 {
+        let position = Position::from_index(index).unwrap();
+        assert_eq!(board[index], board[position]);
+    }
+    assert_eq!(Position::from_index(10), Ok(Position((0, 1))));
+    assert_eq!(Position::from_index(19), Ok(Position((9, 1))));
 }
+
 #[test]
 fn test_indexing_usize() {
     let board = Board::new();
@@ -148,3 +318,159 @@ fn test_filter_black_pawns() {
     assert_eq!(white_pawns[2], (5, &Piece::BlackPawn));
     assert_eq!(white_pawns.last(), Some(&(38, &Piece::BlackPawn)));
 }
+
+#[test]
+fn test_apply_step() {
+    let mut board = Board::new();
+    let mut turn = Move::from((1, 6));
+    turn.step((0, 5), None);
+    board.apply(&turn);
+    assert_eq!(board[(1, 6)], Piece::Empty);
+    assert_eq!(board[(0, 5)], Piece::WhitePawn);
+}
+
+#[test]
+fn test_apply_removes_kills() {
+    let mut board = board_with(&[
+        ((1, 8), Piece::WhitePawn),
+        ((2, 7), Piece::BlackPawn),
+        ((4, 5), Piece::BlackQueen),
+    ]);
+    let turn = board.legal_moves(PieceColor::White).remove(0);
+    board.apply(&turn);
+    assert_eq!(board, board_with(&[((5, 4), Piece::WhitePawn)]));
+}
+
+#[test]
+fn test_apply_promotes() {
+    let mut board = board_with(&[((3, 1), Piece::WhitePawn), ((6, 8), Piece::BlackPawn)]);
+    let mut turn = Move::from((3, 1));
+    turn.step((2, 0), None);
+    board.apply(&turn);
+    assert_eq!(board[(2, 0)], Piece::WhiteQueen);
+
+    let mut turn = Move::from((6, 8));
+    turn.step((7, 9), None);
+    board.apply(&turn);
+    assert_eq!(board[(7, 9)], Piece::BlackQueen);
+}
+
+#[test]
+fn test_no_promotion_when_passing_the_last_row() {
+    let mut board = board_with(&[
+        ((5, 2), Piece::WhitePawn),
+        ((4, 1), Piece::BlackPawn),
+        ((2, 1), Piece::BlackPawn),
+    ]);
+    let turn = board.legal_moves(PieceColor::White).remove(0);
+    assert_eq!(turn.end_position, Position((1, 2)));
+    board.apply(&turn);
+    assert_eq!(board[(1, 2)], Piece::WhitePawn);
+}
+
+#[test]
+fn test_undo_restores_the_board() {
+    let original = board_with(&[
+        ((1, 8), Piece::WhitePawn),
+        ((2, 7), Piece::BlackPawn),
+        ((2, 1), Piece::BlackPawn),
+        ((4, 5), Piece::BlackQueen),
+        ((3, 2), Piece::WhitePawn),
+    ]);
+    let mut board = original.clone();
+    for turn in original.legal_moves(PieceColor::White) {
+        let undo = board.apply(&turn);
+        assert_ne!(board, original);
+        board.undo(undo);
+        assert_eq!(board, original);
+    }
+}
+
+#[test]
+fn test_undo_promotion() {
+    let original = board_with(&[((3, 1), Piece::WhitePawn)]);
+    let mut board = original.clone();
+    let mut turn = Move::from((3, 1));
+    turn.step((4, 0), None);
+    let undo = board.apply(&turn);
+    assert_eq!(board[(4, 0)], Piece::WhiteQueen);
+    board.undo(undo);
+    assert_eq!(board, original);
+}
+
+#[test]
+fn test_position_hash() {
+    let board = Board::new();
+    let hash = board.position_hash(PieceColor::White);
+    assert_eq!(Board::new().position_hash(PieceColor::White), hash);
+    assert_ne!(board.position_hash(PieceColor::Black), hash);
+
+    let mut turn = Move::from((1, 6));
+    turn.step((0, 5), None);
+    let mut moved = board.clone();
+    let undo = moved.apply(&turn);
+    assert_ne!(moved.position_hash(PieceColor::White), hash);
+    moved.undo(undo);
+    assert_eq!(moved.position_hash(PieceColor::White), hash);
+
+    let mut set = Board::new();
+    set.set((1, 6), Piece::Empty);
+    set.set((0, 5), Piece::WhitePawn);
+    assert_eq!(
+        set.position_hash(PieceColor::Black),
+        Board::from_fn(|x, y| set[(x, y)]).position_hash(PieceColor::Black)
+    );
+    set.set((0, 5), Piece::Empty);
+    set.set((1, 6), Piece::WhitePawn);
+    assert_eq!(set, Board::new());
+}
+
+#[test]
+fn test_serde_round_trip() {
+    let board = Board::new();
+    let json = serde_json::to_value(&board).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 10);
+    assert_eq!(json[0][1], "BP");
+    assert_eq!(json[9][0], "WP");
+    assert_eq!(json[4][5], "--");
+
+    let parsed = Board::deserialize_for(GameType::International, &json).unwrap();
+    assert_eq!(parsed, board);
+
+    let json: serde_json::Value = serde_json::from_str(r#"[["--"]]"#).unwrap();
+    assert!(Board::deserialize_for(GameType::International, &json).is_err());
+}
+
+#[test]
+fn test_english_board() {
+    let board = Board::initial(GameType::English);
+    assert_eq!(board.size(), 8);
+    assert_eq!(board.game_type(), GameType::English);
+    assert_eq!(board.iter().filter(|piece| piece.is_black()).count(), 12);
+    assert_eq!(board.iter().filter(|piece| piece.is_white()).count(), 12);
+    assert_eq!(board[(1, 0)], Piece::BlackPawn);
+    assert_eq!(board[(0, 7)], Piece::WhitePawn);
+    assert_eq!(board[(0, 3)], Piece::Empty);
+
+    let json = serde_json::to_value(&board).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 8);
+    let parsed = Board::deserialize_for(GameType::English, &json).unwrap();
+    assert_eq!(parsed, board);
+    assert_ne!(parsed, Board::new());
+}
+
+#[test]
+fn test_deserialize_for() {
+    let board = Board::initial(GameType::Russian);
+    let json = serde_json::to_value(&board).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 8);
+    let parsed = Board::deserialize_for(GameType::Russian, &json).unwrap();
+    assert_eq!(parsed, board);
+    assert_eq!(parsed.game_type(), GameType::Russian);
+    assert_ne!(
+        Board::deserialize_for(GameType::English, &json).unwrap(),
+        board
+    );
+
+    assert!(Board::deserialize_for(GameType::International, &json).is_err());
+}
@@ -0,0 +1,539 @@
+use std::cmp::Reverse;
+
+use crate::board::cell::Cell;
+use crate::board::piece::{Color, Piece};
+use crate::board::position::Position;
+use crate::board::rules::{Capture, Promotion};
+use crate::board::turn::Move;
+use crate::board::Board;
+
+impl Board {
+    /// returns all the legal moves of the `color` side, following the rules of the board
+    ///
+    /// Capturing is mandatory. In international draughts only the captures taking the most
+    /// pieces are legal (majority rule), other game types may let any capture be chosen.
+    /// Frisian draughts weigh the pieces instead, see `capture_value`.
+    /// Pawns step forward only, and capture backwards too where the rules allow it.
+    /// Frisian pieces capture along rows and columns as well, moving along diagonals only.
+    /// A pawn reaching the last row during a capture goes on as a queen in Russian draughts,
+    /// as a pawn elsewhere.
+    /// Flying queens go any distance along a diagonal and capture a distant piece,
+    /// landing on any empty square behind it; short ones step and jump a single square,
+    /// in every direction.
+    pub fn legal_moves(&self, color: Color) -> Vec<Move> {
+        let pieces = self.positions_of(color);
+
+        let mut captures = Vec::new();
+        for &position in &pieces {
+            self.collect_captures(self[position], Move::from(position), &mut captures);
+        }
+
+        if captures.is_empty() {
+            return pieces
+                .into_iter()
+                .flat_map(|position| self.steps(position))
+                .collect();
+        }
+        match self.rules().capture {
+            Capture::Free => {}
+            Capture::Majority => {
+                let longest = captures.iter().max().cloned();
+                captures.retain(|capture| Some(capture) == longest.as_ref());
+            }
+            Capture::Value => {
+                let best = captures
+                    .iter()
+                    .map(|capture| self.capture_value(capture))
+                    .max();
+                captures.retain(|capture| Some(self.capture_value(capture)) == best);
+            }
+        }
+        dedup(captures)
+    }
+
+    /// returns the worth of `capture` under the value rule, greater for the captures to prefer.
+    /// Kings count as two pawns less a little, the fewer kings the better between captures
+    /// taking as much, and a capture made by a king beats the same one made by a pawn.
+    fn capture_value(&self, capture: &Move) -> (usize, Reverse<usize>, bool) {
+        let kings = capture
+            .kills
+            .iter()
+            .filter(|&&killed| self[killed].is_queen())
+            .count();
+        let pawns = capture.kills.len() - kings;
+        (
+            pawns + 2 * kings,
+            Reverse(kings),
+            self[capture.starting_position].is_queen(),
+        )
+    }
+
+    /// returns positions of all the pieces of given `color`
+    pub fn positions_of(&self, color: Color) -> Vec<Position> {
+        let size = self.size();
+        (0..size)
+            .flat_map(|x| (0..size).map(move |y| Position((x, y))))
+            .filter(|&position| self[position].color() == color)
+            .collect()
+    }
+
+    /// returns the number of moves `color` could make if it had no capture
+    pub fn mobility(&self, color: Color) -> usize {
+        self.positions_of(color)
+            .into_iter()
+            .map(|position| self.steps(position).len())
+            .sum()
+    }
+
+    fn steps(&self, from: Position) -> Vec<Move> {
+        let piece = self[from];
+        let size = self.size();
+        let step = |to| {
+            let mut turn = Move::from(from);
+            turn.step(to, None);
+            turn
+        };
+
+        if piece.is_queen() && self.rules().flying_kings {
+            return from
+                .possible_moves_on(None, size)
+                .into_iter()
+                .flat_map(|towards| {
+                    from.ray_on(towards, size)
+                        .into_iter()
+                        .take_while(|&to| self[to].is_empty())
+                })
+                .map(step)
+                .collect();
+        }
+
+        from.possible_moves_on(None, size)
+            .into_iter()
+            .filter(|&to| {
+                self[to].is_empty() && (piece.is_queen() || is_forward(piece.color(), from, to))
+            })
+            .map(step)
+            .collect()
+    }
+
+    /// extends `turn` with every possible jump, pushing the finished chains into `captures`.
+    ///
+    /// Captured pieces stay on the board until the move is over, so they can be neither
+    /// jumped twice nor jumped through. A capture has to be followed as long as it can:
+    /// a queen able to go on from some of the cells behind a taken piece has to land
+    /// on one of those.
+    fn collect_captures(&self, piece: Piece, turn: Move, captures: &mut Vec<Move>) {
+        let jumps = self.jumps(piece, &turn);
+        if jumps.is_empty() {
+            if !turn.kills.is_empty() {
+                captures.push(turn);
+            }
+            return;
+        }
+
+        for (over, landings) in jumps {
+            let nexts: Vec<(Piece, Move, bool)> = landings
+                .into_iter()
+                .map(|landing| {
+                    let mut next = turn.clone();
+                    next.step(landing, over);
+                    let piece = self.crowned(piece, landing);
+                    let goes_on = !self.jumps(piece, &next).is_empty();
+                    (piece, next, goes_on)
+                })
+                .collect();
+            let must_go_on = nexts.iter().any(|&(_, _, goes_on)| goes_on);
+            for (piece, next, goes_on) in nexts {
+                if goes_on || !must_go_on {
+                    self.collect_captures(piece, next, captures);
+                }
+            }
+        }
+    }
+
+    /// returns the pieces `piece` can take from the end of `turn`,
+    /// each with the cells it may land on behind it
+    fn jumps(&self, piece: Piece, turn: &Move) -> Vec<(Position, Vec<Position>)> {
+        let rules = self.rules();
+        let size = self.size();
+        let from = turn.end_position;
+        let reach = if piece.is_queen() && rules.flying_kings {
+            size
+        } else {
+            1
+        };
+
+        let mut directions = from.possible_moves_on(None, size);
+        if rules.orthogonal_captures {
+            directions.extend(from.orthogonal_moves_on(size));
+        }
+
+        let mut jumps = Vec::new();
+        for towards in directions {
+            if piece.is_pawn()
+                && !rules.pawns_capture_backwards
+                && !is_forward(piece.color(), from, towards)
+            {
+                continue;
+            }
+            let ray = from.ray_on(towards, size);
+            let distance = match ray.iter().position(|&cell| !self.is_vacant(cell, turn)) {
+                Some(distance) if distance < reach => distance,
+                _ => continue,
+            };
+            let over = ray[distance];
+            if !piece.is_enemy(&self[over]) || turn.kills.contains(&over) {
+                continue;
+            }
+
+            let landings: Vec<Position> = ray[distance + 1..]
+                .iter()
+                .take_while(|&&cell| self.is_vacant(cell, turn))
+                .take(reach)
+                .copied()
+                .collect();
+            if !landings.is_empty() {
+                jumps.push((over, landings));
+            }
+        }
+        jumps
+    }
+
+    /// returns `piece` as it goes on after landing on `cell` during a capture,
+    /// promoted if the rules crown pawns reaching the last row right away
+    fn crowned(&self, piece: Piece, cell: Position) -> Piece {
+        let mut piece = piece;
+        if self.rules().promotion == Promotion::DuringCapture
+            && self.is_last_row(cell, piece.color())
+        {
+            piece.promote();
+        }
+        piece
+    }
+
+    /// returns `true` if nothing stands on `cell` during `turn`,
+    /// the square the piece started from counts as vacant
+    fn is_vacant(&self, cell: Position, turn: &Move) -> bool {
+        self[cell].is_empty() || cell == turn.starting_position
+    }
+}
+
+/// returns `true` if going from `from` to `to` advances a pawn of given `color`.
+/// Black pawns go down the board (growing `y`), white ones go up.
+fn is_forward(color: Color, from: Position, to: Position) -> bool {
+    let (_, from_y) = from.inner();
+    let (_, to_y) = to.inner();
+    match color {
+        Color::Black => to_y > from_y,
+        Color::White => to_y < from_y,
+        Color::None => false,
+    }
+}
+
+/// removes the captures reaching the same square by taking the same pieces in another order
+fn dedup(moves: Vec<Move>) -> Vec<Move> {
+    let mut unique: Vec<Move> = Vec::with_capacity(moves.len());
+    for turn in moves {
+        if !unique.iter().any(|other| other.is_equivalent(&turn)) {
+            unique.push(turn);
+        }
+    }
+    unique
+}
+
+#[cfg(test)]
+use crate::board::board_with;
+
+#[test]
+fn test_initial_moves() {
+    let board = Board::new();
+    let white = board.legal_moves(Color::White);
+    assert_eq!(white.len(), 9);
+    assert!(white.iter().all(|turn| turn.kills.is_empty()));
+    assert!(white.iter().all(|turn| turn.starting_position.inner().1 == 6));
+
+    let black = board.legal_moves(Color::Black);
+    assert_eq!(black.len(), 9);
+    assert!(black.iter().all(|turn| turn.end_position.inner().1 == 4));
+}
+
+#[test]
+fn test_pawn_captures_backwards() {
+    let board = board_with(&[((4, 5), Piece::WhitePawn), ((5, 6), Piece::BlackPawn)]);
+    let moves = board.legal_moves(Color::White);
+    assert_eq!(moves.len(), 1);
+    assert_eq!(moves[0].starting_position, Position((4, 5)));
+    assert_eq!(moves[0].end_position, Position((6, 7)));
+    assert_eq!(moves[0].kills, vec![Position((5, 6))]);
+}
+
+#[test]
+fn test_capture_chain() {
+    let board = board_with(&[
+        ((1, 8), Piece::WhitePawn),
+        ((2, 7), Piece::BlackPawn),
+        ((4, 5), Piece::BlackPawn),
+    ]);
+    let moves = board.legal_moves(Color::White);
+    assert_eq!(moves.len(), 1);
+    assert_eq!(moves[0].end_position, Position((5, 4)));
+    assert_eq!(moves[0].moves_counter, 2);
+    assert_eq!(moves[0].kills, vec![Position((2, 7)), Position((4, 5))]);
+}
+
+#[test]
+fn test_majority_capture() {
+    let board = board_with(&[
+        ((1, 8), Piece::WhitePawn),
+        ((2, 7), Piece::BlackPawn),
+        ((4, 5), Piece::BlackPawn),
+        ((7, 8), Piece::WhitePawn),
+        ((8, 7), Piece::BlackPawn),
+    ]);
+    let moves = board.legal_moves(Color::White);
+    assert_eq!(moves.len(), 1);
+    assert_eq!(moves[0].starting_position, Position((1, 8)));
+    assert_eq!(moves[0].kills.len(), 2);
+}
+
+#[test]
+fn test_capture_around_the_square() {
+    // the pawn may land on its own starting square, but it can not take (4, 7) twice;
+    // going around clockwise and counter-clockwise is the same move
+    let board = board_with(&[
+        ((5, 8), Piece::WhitePawn),
+        ((4, 7), Piece::BlackPawn),
+        ((4, 5), Piece::BlackPawn),
+        ((6, 5), Piece::BlackPawn),
+        ((6, 7), Piece::BlackPawn),
+    ]);
+    let moves = board.legal_moves(Color::White);
+    assert_eq!(moves.len(), 1);
+    assert_eq!(moves[0].kills.len(), 4);
+    assert_eq!(moves[0].end_position, Position((5, 8)));
+}
+
+#[test]
+fn test_black_pawns_step_down() {
+    let board = board_with(&[((0, 9), Piece::WhitePawn), ((3, 2), Piece::BlackPawn)]);
+    let moves = board.legal_moves(Color::Black);
+    let mut ends: Vec<_> = moves.iter().map(|turn| turn.end_position).collect();
+    ends.sort();
+    assert_eq!(ends, vec![Position((2, 3)), Position((4, 3))]);
+}
+
+#[test]
+fn test_queen_flies() {
+    let board = board_with(&[((0, 9), Piece::WhiteQueen), ((5, 4), Piece::WhitePawn)]);
+    let moves = board.legal_moves(Color::White);
+    let ends: Vec<_> = moves
+        .iter()
+        .filter(|turn| turn.starting_position == Position((0, 9)))
+        .map(|turn| turn.end_position)
+        .collect();
+    assert_eq!(
+        ends,
+        vec![
+            Position((1, 8)),
+            Position((2, 7)),
+            Position((3, 6)),
+            Position((4, 5)),
+        ]
+    );
+}
+
+#[test]
+fn test_queen_captures_from_distance() {
+    let board = board_with(&[((0, 9), Piece::WhiteQueen), ((4, 5), Piece::BlackPawn)]);
+    let moves = board.legal_moves(Color::White);
+    let mut ends: Vec<_> = moves.iter().map(|turn| turn.end_position).collect();
+    ends.sort();
+    assert_eq!(
+        ends,
+        vec![
+            Position((5, 4)),
+            Position((6, 3)),
+            Position((7, 2)),
+            Position((8, 1)),
+            Position((9, 0)),
+        ]
+    );
+    assert!(moves.iter().all(|turn| turn.kills == vec![Position((4, 5))]));
+}
+
+#[test]
+fn test_queen_capture_chain() {
+    // after taking (2, 7) the queen has to land on (4, 5) to go on and take (6, 7)
+    let board = board_with(&[
+        ((0, 9), Piece::WhiteQueen),
+        ((2, 7), Piece::BlackPawn),
+        ((6, 7), Piece::BlackPawn),
+    ]);
+    let moves = board.legal_moves(Color::White);
+    assert!(!moves.is_empty());
+    assert!(moves.iter().all(|turn| turn.kills.len() == 2));
+    let mut ends: Vec<_> = moves.iter().map(|turn| turn.end_position).collect();
+    ends.sort();
+    assert_eq!(ends, vec![Position((7, 8)), Position((8, 9))]);
+}
+
+#[test]
+fn test_queen_can_not_jump_two_pieces() {
+    let board = board_with(&[
+        ((0, 9), Piece::WhiteQueen),
+        ((3, 6), Piece::BlackPawn),
+        ((4, 5), Piece::BlackPawn),
+    ]);
+    let moves = board.legal_moves(Color::White);
+    assert!(moves.iter().all(|turn| turn.kills.is_empty()));
+    assert_eq!(moves.len(), 2);
+}
+
+#[cfg(test)]
+use crate::board::rules::GameType;
+
+/// returns the board of `game_type` with the position given in FEN
+#[cfg(test)]
+fn board_for(game_type: GameType, fen: &str) -> Board {
+    Board::from_fen_for(fen, game_type).unwrap().0
+}
+
+/// returns the notations of the legal moves of white, sorted
+#[cfg(test)]
+fn white_notations(board: &Board) -> Vec<String> {
+    let mut notations: Vec<String> = board
+        .legal_moves(Color::White)
+        .iter()
+        .map(|turn| turn.notation(board.size()))
+        .collect();
+    notations.sort();
+    notations
+}
+
+#[test]
+fn test_english_pawns_capture_forwards_only() {
+    // the black pawn on 22 stands behind the white one on 18, out of its reach
+    let board = board_for(GameType::English, "W:W18:B22");
+    let moves = board.legal_moves(Color::White);
+    assert_eq!(moves.len(), 2);
+    assert!(moves.iter().all(|turn| turn.kills.is_empty()));
+
+    let moves = board.legal_moves(Color::Black);
+    assert_eq!(moves.len(), 2);
+    let board = board_for(GameType::English, "B:W18:B14");
+    let moves = board.legal_moves(Color::Black);
+    assert_eq!(moves.len(), 1);
+    assert_eq!(moves[0].notation(8), "14x23");
+}
+
+#[test]
+fn test_english_free_choice_of_capture() {
+    // 26x17 taking one piece stays legal next to the captures taking two
+    let board = board_for(GameType::English, "W:W26,27:B15,22,23");
+    assert_eq!(
+        white_notations(&board),
+        vec!["26x17", "26x19x10", "27x18x11"]
+    );
+}
+
+#[test]
+fn test_english_short_kings() {
+    let board = board_for(GameType::English, "W:WK29:B");
+    let moves = board.legal_moves(Color::White);
+    assert_eq!(moves.len(), 1);
+    assert_eq!(moves[0].notation(8), "29-25");
+
+    // the king can not take 15 from a distance, but it jumps backwards over 26
+    let board = board_for(GameType::English, "W:WK22:B15,26");
+    let moves = board.legal_moves(Color::White);
+    assert_eq!(moves.len(), 1);
+    assert_eq!(moves[0].notation(8), "22x31");
+}
+
+#[test]
+fn test_russian_promotion_during_capture() {
+    // the pawn is crowned on 2 and goes on flying over 6 to take 9
+    let mut board = board_for(GameType::Russian, "W:W11:B7,9");
+    assert_eq!(white_notations(&board), vec!["11x2x13"]);
+    let turn = board.legal_moves(Color::White).remove(0);
+    board.apply(&turn);
+    assert_eq!(board.to_fen(Color::Black), "B:WK13:B");
+
+    // in Brazilian and pool checkers it stays a pawn until the capture ends
+    for &game_type in &[GameType::Brazilian, GameType::Pool] {
+        let mut board = board_for(game_type, "W:W11:B7,9");
+        assert_eq!(white_notations(&board), vec!["11x2"]);
+        let turn = board.legal_moves(Color::White).remove(0);
+        board.apply(&turn);
+        assert_eq!(board.to_fen(Color::Black), "B:WK2:B9");
+    }
+}
+
+#[test]
+fn test_pool_pawn_passes_the_last_row() {
+    let mut board = board_for(GameType::Pool, "W:W11:B6,7");
+    assert_eq!(white_notations(&board), vec!["11x2x9"]);
+    let turn = board.legal_moves(Color::White).remove(0);
+    board.apply(&turn);
+    assert_eq!(board.to_fen(Color::Black), "B:W9:B");
+}
+
+#[test]
+fn test_capture_choice() {
+    // 26x17 takes a single piece, which only the majority rule forbids
+    let fen = "W:W26,27:B15,22,23";
+    assert_eq!(
+        white_notations(&board_for(GameType::Brazilian, fen)),
+        vec!["26x19x10", "27x18x11", "27x18x25"]
+    );
+    for &game_type in &[GameType::Russian, GameType::Pool] {
+        assert_eq!(
+            white_notations(&board_for(game_type, fen)),
+            vec!["26x17", "26x19x10", "27x18x11", "27x18x25"]
+        );
+    }
+}
+
+#[test]
+fn test_king_lands_where_the_capture_goes_on() {
+    // behind 22 only 15 lets the king take 24 as well
+    let board = board_for(GameType::Russian, "W:WK29:B22,24");
+    assert_eq!(white_notations(&board), vec!["29x15x28"]);
+}
+
+#[test]
+fn test_canadian_initial_moves() {
+    let board = Board::initial(GameType::Canadian);
+    assert_eq!(board.positions_of(Color::White).len(), 30);
+    let moves = board.legal_moves(Color::White);
+    assert_eq!(moves.len(), 11);
+    assert!(moves.iter().all(|turn| turn.end_position.inner().1 == 6));
+}
+
+#[test]
+fn test_frisian_orthogonal_captures() {
+    // the pawn on 28 takes 27 along its row, then 16 along its column
+    let board = board_for(GameType::Frisian, "W:W28:B16,27");
+    assert_eq!(white_notations(&board), vec!["28x26x6"]);
+    assert_eq!(
+        white_notations(&board_for(GameType::International, "W:W28:B16,27")),
+        vec!["28-22", "28-23"]
+    );
+
+    // kings capture from a distance along rows too
+    let board = board_for(GameType::Frisian, "W:WK46:B48");
+    assert_eq!(white_notations(&board), vec!["46x49", "46x50"]);
+}
+
+#[test]
+fn test_frisian_capture_value() {
+    // a king is worth more than a pawn
+    let board = board_for(GameType::Frisian, "W:W32,34:B27,K29");
+    assert_eq!(white_notations(&board), vec!["34x23"]);
+    // but less than two pawns
+    let board = board_for(GameType::Frisian, "W:W32,34:B17,27,K29");
+    assert_eq!(white_notations(&board), vec!["32x21x12"]);
+    // with the same value the capture made by a king wins
+    let board = board_for(GameType::Frisian, "W:W32,34,K46:B29,41");
+    assert_eq!(white_notations(&board), vec!["46x37"]);
+}
@@ -0,0 +1,204 @@
+use crate::board::bitboard::BitBoard;
+use crate::board::piece::Color;
+use crate::board::rules::GameType;
+use crate::board::search::SearchPosition;
+use crate::board::turn::Move;
+use crate::board::Board;
+
+/// counts the positions reached after `depth` plies from `position` with `color` to move,
+/// every sequence of legal moves counting once
+pub fn perft<P>(position: &P, color: Color, depth: usize) -> u64
+where
+    P: SearchPosition,
+{
+    if depth == 0 {
+        return 1;
+    }
+    let moves = position.moves(color);
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+    moves
+        .iter()
+        .map(|turn| perft(&position.after(turn), color.opposite(), depth - 1))
+        .sum()
+}
+
+/// returns the perft count below each legal move, `depth` counting the move itself
+pub fn divide<P>(position: &P, color: Color, depth: usize) -> Vec<(P::Move, u64)>
+where
+    P: SearchPosition,
+{
+    position
+        .moves(color)
+        .into_iter()
+        .map(|turn| {
+            let count = perft(&position.after(&turn), color.opposite(), depth.max(1) - 1);
+            (turn, count)
+        })
+        .collect()
+}
+
+impl Board {
+    /// counts the positions reached after `depth` plies with `color` to move,
+    /// walking the tree on a `BitBoard` for international positions
+    pub fn perft(&self, color: Color, depth: usize) -> u64 {
+        if self.game_type() != GameType::International {
+            return perft(self, color, depth);
+        }
+        perft(&BitBoard::from(self), color, depth)
+    }
+
+    /// returns the perft count below each legal move of `color`
+    pub fn divide(&self, color: Color, depth: usize) -> Vec<(Move, u64)> {
+        if self.game_type() != GameType::International {
+            return divide(self, color, depth);
+        }
+        let legal = self.legal_moves(color);
+        divide(&BitBoard::from(self), color, depth)
+            .into_iter()
+            .map(|(bit_move, count)| {
+                let turn = legal
+                    .iter()
+                    .find(|turn| bit_move.matches(turn))
+                    .expect("both boards have the same legal moves");
+                (turn.clone(), count)
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_initial_position() {
+    let expected = [1, 9, 81, 658, 4265, 27117, 167_140, 1_049_442];
+    let board = Board::new();
+    for (depth, &count) in expected.iter().enumerate() {
+        assert_eq!(board.perft(Color::White, depth), count, "depth {}", depth);
+    }
+    for (depth, &count) in expected.iter().enumerate().take(5) {
+        assert_eq!(perft(&board, Color::White, depth), count, "depth {}", depth);
+    }
+}
+
+#[test]
+fn test_divide() {
+    let board = Board::new();
+    let divided = board.divide(Color::White, 2);
+    assert_eq!(divided.len(), 9);
+    assert!(divided.iter().all(|(_, count)| *count == 9));
+
+    let divided = board.divide(Color::White, 4);
+    let total: u64 = divided.iter().map(|(_, count)| count).sum();
+    assert_eq!(total, 4265);
+}
+
+/// checks the perft counts of the position given in FEN, which start with depth 1,
+/// and that the matrix board walks the same tree
+#[cfg(test)]
+fn assert_perft(fen: &str, expected: &[u64]) {
+    let (board, color) = Board::from_fen(fen).unwrap();
+    for (depth, &count) in expected.iter().enumerate() {
+        let depth = depth + 1;
+        assert_eq!(board.perft(color, depth), count, "{} depth {}", fen, depth);
+        assert_eq!(
+            perft(&board, color, depth),
+            count,
+            "{} depth {}",
+            fen,
+            depth
+        );
+    }
+}
+
+#[test]
+fn test_majority_capture() {
+    // 32x21 takes one piece, 32x23x12 takes two and is the only legal move
+    assert_perft("W:W32:B18,27,28", &[1, 2]);
+}
+
+#[test]
+fn test_king_captures() {
+    // the king may stop on any square behind 28, but only from 19 it goes on taking 24
+    assert_perft("W:WK46:B1,24,28", &[2, 4]);
+    assert_perft("W:WK46:B28", &[5]);
+}
+
+#[test]
+fn test_promotion_during_capture() {
+    // the pawn passes square 2 but stays a pawn, so it can not fly on to 16
+    assert_perft("W:W13:B7,8", &[1]);
+    let (board, color) = Board::from_fen("W:W13:B7,8").unwrap();
+    let (turn, _) = &board.divide(color, 1)[0];
+    assert_eq!(turn.notation(board.size()), "13x2x11");
+    let mut after = board.clone();
+    after.apply(turn);
+    assert_eq!(after.to_fen(Color::Black), "B:W11:B");
+
+    // finishing the capture on the last row promotes
+    let (mut board, _) = Board::from_fen("W:W13:B8").unwrap();
+    let (turn, _) = board.divide(Color::White, 1).remove(0);
+    board.apply(&turn);
+    assert_eq!(board.to_fen(Color::Black), "B:WK2:B");
+}
+
+#[test]
+fn test_middle_game() {
+    let (board, color) = Board::from_fen("W:WK10,K15,18,24,27-28:B12,16,20,K22,K25,K29").unwrap();
+    for depth in 1..=3 {
+        assert_eq!(board.perft(color, depth), perft(&board, color, depth));
+    }
+}
+
+#[test]
+fn test_published_positions() {
+    // reference counts published for 10x10 draughts with the perft tables of Ed Gilbert
+    // and Rein Halbersma. The first position has kings capturing on both sides and white
+    // pawns a step away from promotion, the second one, the Woldouby position, is full
+    // of forced majority captures.
+    assert_perft(
+        "B:W6,9,10,11,20,21,22,23,30,K31,33,37,41,42,43,44,46:BK17,K24",
+        &[14, 55, 1168, 5432, 87195],
+    );
+    assert_perft(
+        "W:W25,27,28,30,32,33,34,35,37,38:B12,13,14,16,18,19,21,23,24,26",
+        &[6, 12, 30, 73, 215, 590, 1944, 6269, 22369],
+    );
+
+    let (board, color) =
+        Board::from_fen("B:W6,9,10,11,20,21,22,23,30,K31,33,37,41,42,43,44,46:BK17,K24").unwrap();
+    assert_eq!(board.perft(color, 6), 629_010);
+    assert_eq!(board.perft(color, 7), 9_041_010);
+}
+
+#[test]
+fn test_english_initial_position() {
+    let board = Board::initial(GameType::English);
+    let expected = [1, 7, 49, 302, 1469, 7361, 36768];
+    for (depth, &count) in expected.iter().enumerate() {
+        assert_eq!(board.perft(Color::Black, depth), count, "depth {}", depth);
+    }
+    assert_eq!(board.divide(Color::Black, 1).len(), 7);
+}
+
+#[test]
+fn test_other_initial_positions() {
+    // the game types only part once backward captures and the majority rule come into play
+    let expected = [
+        (GameType::Russian, [1, 7, 49, 302, 1469, 7482, 37986]),
+        (GameType::Brazilian, [1, 7, 49, 302, 1469, 7473, 37628]),
+        (GameType::Pool, [1, 7, 49, 302, 1469, 7482, 37986]),
+    ];
+    for &(game_type, counts) in &expected {
+        let board = Board::initial(game_type);
+        let color = game_type.rules().first;
+        for (depth, &count) in counts.iter().enumerate() {
+            assert_eq!(
+                board.perft(color, depth),
+                count,
+                "{:?} depth {}",
+                game_type,
+                depth
+            );
+        }
+    }
+}
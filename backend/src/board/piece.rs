@@ -1,13 +1,22 @@
 use std::fmt;
 
+use serde::{Deserialize, Serialize};
+
 use crate::board::cell::Cell;
 
-#[derive(Debug, Copy, Clone, PartialOrd, PartialEq, Eq, Ord)]
+/// Piece standing on a cell, serialized as the short code of its display:
+/// `"WP"`, `"BP"`, `"WQ"`, `"BQ"` or `"--"` for an empty cell
+#[derive(Debug, Copy, Clone, PartialOrd, PartialEq, Eq, Ord, Serialize, Deserialize)]
 pub enum Piece {
+    #[serde(rename = "WP")]
     WhitePawn = 1,
+    #[serde(rename = "BP")]
     BlackPawn = 2,
+    #[serde(rename = "WQ")]
     WhiteQueen = 11,
+    #[serde(rename = "BQ")]
     BlackQueen = 12,
+    #[serde(rename = "--")]
     Empty = 0,
 }
 
@@ -18,7 +27,9 @@ pub enum Variant {
     None
 }
 
-#[derive(Debug, Copy, Clone, PartialOrd, PartialEq)]
+/// Side of the game, serialized as `"white"`, `"black"` or `"none"`
+#[derive(Debug, Copy, Clone, PartialOrd, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Color {
     Black,
     White,
@@ -28,6 +39,7 @@ pub enum Color {
 impl Piece {
     /// promotes the pawn to queen
     /// ```
+    /// # use backend::board::piece::Piece;
     /// let mut piece = Piece::BlackPawn;
     /// piece.promote();
     /// assert_eq!(piece, Piece::BlackQueen);
@@ -41,6 +53,17 @@ impl Piece {
     }
 }
 
+impl Color {
+    /// returns the color of the opponent, `None` stays `None`
+    pub fn opposite(self) -> Self {
+        match self {
+            Color::Black => Color::White,
+            Color::White => Color::Black,
+            Color::None => Color::None,
+        }
+    }
+}
+
 impl fmt::Display for Piece {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use Piece::*;
@@ -96,11 +119,13 @@ impl Cell for Piece {
     /// returns `PieceVariant` of the piece
     ///
     /// ```
-    /// assert_eq!(Piece::Empty.piece_type(), PieceType::None);
-    /// assert_eq!(Piece::WhitePawn.piece_type(), PieceType::Pawn);
-    /// assert_eq!(Piece::BlackPawn.piece_type(), PieceType::Pawn);
-    /// assert_eq!(Piece::BlackQueen.piece_type(), PieceType::Queen);
-    /// assert_eq!(Piece::WhiteQueen.piece_type(), PieceType::Queen);
+    /// # use backend::board::cell::Cell;
+    /// # use backend::board::piece::{Piece, Variant};
+    /// assert_eq!(Piece::Empty.variant(), Variant::None);
+    /// assert_eq!(Piece::WhitePawn.variant(), Variant::Pawn);
+    /// assert_eq!(Piece::BlackPawn.variant(), Variant::Pawn);
+    /// assert_eq!(Piece::BlackQueen.variant(), Variant::Queen);
+    /// assert_eq!(Piece::WhiteQueen.variant(), Variant::Queen);
     /// ```
     fn variant(&self) -> Self::Variant {
         if self.is_pawn() {
@@ -200,6 +225,13 @@ fn variant_works() {
     assert_eq!(Piece::WhiteQueen.variant(), Variant::Queen);
 }
 
+#[test]
+fn opposite_works() {
+    assert_eq!(Color::White.opposite(), Color::Black);
+    assert_eq!(Color::Black.opposite(), Color::White);
+    assert_eq!(Color::None.opposite(), Color::None);
+}
+
 #[test]
 fn color_works() {
     assert_eq!(Piece::Empty.color(), Color::None);
@@ -208,3 +240,25 @@ fn color_works() {
     assert_eq!(Piece::BlackQueen.color(), Color::Black);
     assert_eq!(Piece::WhiteQueen.color(), Color::White);
 }
+
+#[test]
+fn serde_round_trip() {
+    let pieces = [
+        Piece::WhitePawn,
+        Piece::BlackPawn,
+        Piece::WhiteQueen,
+        Piece::BlackQueen,
+        Piece::Empty,
+    ];
+    let json = serde_json::to_string(&pieces).unwrap();
+    assert_eq!(json, r#"["WP","BP","WQ","BQ","--"]"#);
+    let parsed: Vec<Piece> = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, pieces);
+
+    assert_eq!(serde_json::to_string(&Color::White).unwrap(), r#""white""#);
+    assert_eq!(
+        serde_json::from_str::<Color>(r#""black""#).unwrap(),
+        Color::Black
+    );
+    assert!(serde_json::from_str::<Piece>(r#""XX""#).is_err());
+}
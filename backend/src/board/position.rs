@@ -1,7 +1,12 @@
+use std::error::Error;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
 use super::MATRIX_SIZE;
 
-/// Wrapper on (x, y) coordinates
-#[derive(Debug, Copy, Clone, PartialOrd, PartialEq)]
+/// Wrapper on (x, y) coordinates, serialized as `[x, y]`
+#[derive(Debug, Copy, Clone, PartialOrd, PartialEq, Eq, Ord, Hash, Serialize, Deserialize)]
 pub struct Position(pub (usize, usize));
 
 impl Position {
@@ -9,35 +14,185 @@ impl Position {
         self.0
     }
 
+    /// returns the cell of the square with given official number.
+    /// Squares are numbered from 1 on the black side to 50 on the white side,
+    /// going through the dark squares of each row from `x = 0` on.
+    pub fn from_square(square: usize) -> Result<Self, PositionError> {
+        Position::from_square_on(square, MATRIX_SIZE)
+    }
+
+    /// returns the cell of the square with given number on a board of `size` cells a side,
+    /// numbered like the squares of the international board
+    pub fn from_square_on(square: usize, size: usize) -> Result<Self, PositionError> {
+        if square == 0 || square > size * size / 2 {
+            return Err(PositionError::InvalidSquare(square));
+        }
+        let per_row = size / 2;
+        let y = (square - 1) / per_row;
+        let x = (square - 1) % per_row * 2 + (1 - y % 2);
+        Ok(Position((x, y)))
+    }
+
+    /// returns the official number of the square
+    pub fn square(&self) -> Result<usize, PositionError> {
+        self.square_on(MATRIX_SIZE)
+    }
+
+    /// returns the number of the square on a board of `size` cells a side
+    pub fn square_on(&self, size: usize) -> Result<usize, PositionError> {
+        let (x, y) = self.within(size)?.inner();
+        if (x + y) % 2 == 0 {
+            return Err(PositionError::LightSquare(*self));
+        }
+        Ok(y * size / 2 + x / 2 + 1)
+    }
+
+    /// returns the cell stored under `index` of the board matrix.
+    /// The matrix keeps its cells column after column, so `index = x + y * 10`.
+    pub fn from_index(index: usize) -> Result<Self, PositionError> {
+        if index >= MATRIX_SIZE * MATRIX_SIZE {
+            return Err(PositionError::InvalidIndex(index));
+        }
+        Ok(Position((index % MATRIX_SIZE, index / MATRIX_SIZE)))
+    }
+
+    /// returns the index of the cell in the board matrix, the inverse of `from_index`
+    pub fn index(&self) -> Result<usize, PositionError> {
+        let (x, y) = self.within(MATRIX_SIZE)?.inner();
+        Ok(x + y * MATRIX_SIZE)
+    }
+
+    fn within(&self, size: usize) -> Result<Self, PositionError> {
+        let (x, y) = self.inner();
+        if x >= size || y >= size {
+            return Err(PositionError::OutOfBoard(*self));
+        }
+        Ok(*self)
+    }
+
+    /// returns the cell `dx` columns and `dy` rows away, if it lies on a board of `size` cells a side
+    fn offset(&self, dx: isize, dy: isize, size: usize) -> Option<Self> {
+        let (x, y) = self.inner();
+        let (x, y) = (x as isize + dx, y as isize + dy);
+        if x < 0 || y < 0 || x >= size as isize || y >= size as isize {
+            return None;
+        }
+        Some(Position((x as usize, y as usize)))
+    }
+
     pub fn possible_moves<O>(&self, previous: O) -> Vec<Self>
     where
         O: Into<Option<Position>>,
     {
+        self.possible_moves_on(previous, MATRIX_SIZE)
+    }
+
+    /// returns the diagonal neighbours of the cell on a board of `size` cells a side,
+    /// leaving out `previous`
+    pub fn possible_moves_on<O>(&self, previous: O, size: usize) -> Vec<Self>
+    where
+        O: Into<Option<Position>>,
+    {
+        let previous = previous.into();
+        [(-1, -1), (-1, 1), (1, -1), (1, 1)]
+            .iter()
+            .filter_map(|&(dx, dy)| self.offset(dx, dy, size))
+            .filter(|pos| previous != Some(*pos))
+            .collect()
+    }
+
+    /// returns the closest dark cells in the row and in the column of the cell,
+    /// two cells away, on a board of `size` cells a side
+    pub fn orthogonal_moves_on(&self, size: usize) -> Vec<Self> {
+        [(-2, 0), (2, 0), (0, -2), (0, 2)]
+            .iter()
+            .filter_map(|&(dx, dy)| self.offset(dx, dy, size))
+            .collect()
+    }
+
+    /// returns the cell lying right behind `over` when looking from `self`,
+    /// i.e. the landing cell of a jump over `over`
+    pub fn beyond(&self, over: Position) -> Option<Self> {
+        self.beyond_on(over, MATRIX_SIZE)
+    }
+
+    /// returns the cell lying right behind `over` on a board of `size` cells a side
+    pub fn beyond_on(&self, over: Position, size: usize) -> Option<Self> {
         let (x, y) = self.inner();
-        let (x, y) = (x as isize, y as isize);
-        let previous = previous
-            .into()
-            .unwrap_or(Position((MATRIX_SIZE + 1, MATRIX_SIZE + 1)));
-        [
-            (x - 1, y - 1).try_convert(),
-            (x - 1, y + 1).try_convert(),
-            (x + 1, y - 1).try_convert(),
-            (x + 1, y + 1).try_convert(),
-        ]
-        .iter()
-        .filter_map(|option| *option)
-        .filter(|pos| &previous != pos)
-        .collect()
+        let (over_x, over_y) = over.inner();
+        over.offset(
+            over_x as isize - x as isize,
+            over_y as isize - y as isize,
+            size,
+        )
+    }
+
+    /// returns cells of the diagonal going from `self` through the neighbouring `towards`,
+    /// up to the edge of the board
+    pub fn ray(&self, towards: Position) -> Vec<Self> {
+        self.ray_on(towards, MATRIX_SIZE)
+    }
+
+    /// returns cells of the line going from `self` through the neighbouring `towards`,
+    /// up to the edge of a board of `size` cells a side.
+    /// The line is a diagonal, or a row or a column for `towards` given by `orthogonal_moves_on`.
+    pub fn ray_on(&self, towards: Position, size: usize) -> Vec<Self> {
+        let mut cells = vec![towards];
+        let mut previous = *self;
+        let mut current = towards;
+        while let Some(next) = previous.beyond_on(current, size) {
+            cells.push(next);
+            previous = current;
+            current = next;
+        }
+        cells
     }
 }
 
-pub trait TryConvert<T> {
-    fn try_convert(self) -> Option<T>;
+/// Reasons a cell has no square number or matrix index, or a number names no cell
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PositionError {
+    /// the square number is not one of the dark squares of the board
+    InvalidSquare(usize),
+    /// the matrix index names no cell of the international board, it is not below 100
+    InvalidIndex(usize),
+    /// the cell lies outside of the board
+    OutOfBoard(Position),
+    /// the cell is a light one, which pieces never stand on
+    LightSquare(Position),
+}
+
+impl fmt::Display for PositionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PositionError::InvalidSquare(square) => write!(f, "no square numbered {}", square),
+            PositionError::InvalidIndex(index) => write!(f, "no cell under index {}", index),
+            PositionError::OutOfBoard(position) => {
+                write!(f, "cell {:?} lies outside of the board", position.inner())
+            }
+            PositionError::LightSquare(position) => {
+                write!(f, "cell {:?} is a light square", position.inner())
+            }
+        }
+    }
+}
+
+impl Error for PositionError {}
+
+/// Conversion of signed coordinates into ones of the board, `None` when they fall outside
+pub trait TryConvert<T>: Sized {
+    /// converts coordinates of the international board
+    fn try_convert(self) -> Option<T> {
+        self.try_convert_on(MATRIX_SIZE)
+    }
+
+    /// converts coordinates of a board of `size` cells a side
+    fn try_convert_on(self, size: usize) -> Option<T>;
 }
 
 impl TryConvert<usize> for isize {
-    fn try_convert(self) -> Option<usize> {
-        if self < 0 || self > MATRIX_SIZE as isize {
+    fn try_convert_on(self, size: usize) -> Option<usize> {
+        if self < 0 || self >= size as isize {
             None
         } else {
             Some(self as usize)
@@ -46,14 +201,17 @@ impl TryConvert<usize> for isize {
 }
 
 impl TryConvert<Position> for (isize, isize) {
-    fn try_convert(self) -> Option<Position> {
-        Some(Position((self.0.try_convert()?, self.1.try_convert()?)))
+    fn try_convert_on(self, size: usize) -> Option<Position> {
+        Some(Position((
+            self.0.try_convert_on(size)?,
+            self.1.try_convert_on(size)?,
+        )))
     }
 }
 
-impl Into<(usize, usize)> for Position {
-    fn into(self) -> (usize, usize) {
-        self.inner()
+impl From<Position> for (usize, usize) {
+    fn from(position: Position) -> Self {
+        position.inner()
     }
 }
 
@@ -63,9 +221,9 @@ impl From<(usize, usize)> for Position {
     }
 }
 
-impl Into<Option<(usize, usize)>> for Position {
-    fn into(self) -> Option<(usize, usize)> {
-        Some(self.into())
+impl From<Position> for Option<(usize, usize)> {
+    fn from(position: Position) -> Self {
+        Some(position.into())
     }
 }
 
@@ -94,4 +252,146 @@ fn possible_moves_boundary() {
     let moves = pos.possible_moves(None);
     assert_eq!(moves.len(), 1);
     assert_eq!(moves[0], Position((1, 1)));
+
+    let pos = Position((9, 9));
+    let moves = pos.possible_moves(None);
+    assert_eq!(moves, vec![Position((8, 8))]);
+}
+
+#[test]
+fn beyond_works() {
+    let pos = Position((3, 3));
+    assert_eq!(pos.beyond(Position((4, 4))), Some(Position((5, 5))));
+    assert_eq!(pos.beyond(Position((2, 4))), Some(Position((1, 5))));
+    assert_eq!(Position((1, 1)).beyond(Position((0, 0))), None);
+    assert_eq!(Position((8, 8)).beyond(Position((9, 9))), None);
+}
+
+#[test]
+fn ray_works() {
+    let pos = Position((2, 3));
+    assert_eq!(
+        pos.ray(Position((3, 4))),
+        vec![
+            Position((3, 4)),
+            Position((4, 5)),
+            Position((5, 6)),
+            Position((6, 7)),
+            Position((7, 8)),
+            Position((8, 9)),
+        ]
+    );
+    assert_eq!(
+        pos.ray(Position((1, 2))),
+        vec![Position((1, 2)), Position((0, 1))]
+    );
+    assert_eq!(Position((1, 0)).ray(Position((0, 1))), vec![Position((0, 1))]);
+}
+
+#[test]
+fn orthogonal_rays() {
+    let pos = Position((3, 4));
+    assert_eq!(
+        pos.orthogonal_moves_on(10),
+        vec![
+            Position((1, 4)),
+            Position((5, 4)),
+            Position((3, 2)),
+            Position((3, 6)),
+        ]
+    );
+    assert_eq!(
+        pos.ray_on(Position((5, 4)), 10),
+        vec![Position((5, 4)), Position((7, 4)), Position((9, 4))]
+    );
+    assert_eq!(
+        pos.ray_on(Position((5, 4)), 12),
+        vec![
+            Position((5, 4)),
+            Position((7, 4)),
+            Position((9, 4)),
+            Position((11, 4)),
+        ]
+    );
+    assert_eq!(
+        Position((1, 0)).orthogonal_moves_on(8),
+        vec![Position((3, 0)), Position((1, 2))]
+    );
+}
+
+#[test]
+fn serde_round_trip() {
+    let pos = Position((3, 6));
+    let json = serde_json::to_string(&pos).unwrap();
+    assert_eq!(json, "[3,6]");
+    assert_eq!(serde_json::from_str::<Position>(&json).unwrap(), pos);
+}
+
+#[test]
+fn square_numbers() {
+    assert_eq!(Position::from_square(1), Ok(Position((1, 0))));
+    assert_eq!(Position::from_square(5), Ok(Position((9, 0))));
+    assert_eq!(Position::from_square(6), Ok(Position((0, 1))));
+    assert_eq!(Position::from_square(46), Ok(Position((0, 9))));
+    assert_eq!(Position::from_square(50), Ok(Position((8, 9))));
+    assert_eq!(
+        Position::from_square(0),
+        Err(PositionError::InvalidSquare(0))
+    );
+    assert_eq!(
+        Position::from_square(51),
+        Err(PositionError::InvalidSquare(51))
+    );
+
+    for square in 1..=50 {
+        assert_eq!(Position::from_square(square).unwrap().square(), Ok(square));
+    }
+    assert_eq!(
+        Position((0, 0)).square(),
+        Err(PositionError::LightSquare(Position((0, 0))))
+    );
+    assert_eq!(
+        Position((11, 0)).square(),
+        Err(PositionError::OutOfBoard(Position((11, 0))))
+    );
+}
+
+#[test]
+fn matrix_indexes() {
+    assert_eq!(Position::from_index(0), Ok(Position((0, 0))));
+    assert_eq!(Position::from_index(9), Ok(Position((9, 0))));
+    assert_eq!(Position::from_index(15), Ok(Position((5, 1))));
+    assert_eq!(Position::from_index(99), Ok(Position((9, 9))));
+    assert_eq!(
+        Position::from_index(100),
+        Err(PositionError::InvalidIndex(100))
+    );
+
+    for index in 0..100 {
+        assert_eq!(Position::from_index(index).unwrap().index(), Ok(index));
+    }
+    assert_eq!(
+        Position((3, 10)).index(),
+        Err(PositionError::OutOfBoard(Position((3, 10))))
+    );
+}
+
+#[test]
+fn try_convert_on_other_boards() {
+    let convert = |x: isize, y: isize, size| (x, y).try_convert_on(size);
+    assert_eq!((9_isize, 9_isize).try_convert(), Some(Position((9, 9))));
+    assert_eq!(convert(10, 11, 12), Some(Position((10, 11))));
+    assert_eq!(convert(7, 8, 8), None);
+    assert_eq!(convert(-1, 0, 8), None);
+}
+
+#[test]
+fn square_numbers_on_other_boards() {
+    assert_eq!(Position((4, 5)).square_on(10), Ok(28));
+    assert_eq!(Position((4, 5)).square_on(8), Ok(23));
+    assert_eq!(Position((4, 5)).square_on(12), Ok(33));
+    assert_eq!(
+        Position((0, 0)).square_on(8),
+        Err(PositionError::LightSquare(Position((0, 0))))
+    );
 }
@@ -0,0 +1,198 @@
+use serde::{Deserialize, Serialize};
+
+use crate::board::piece::Color;
+
+/// Rules a game is played by, serialized in snake case like `"international"` or `"pool"`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameType {
+    /// international draughts on a 10x10 board
+    #[default]
+    International,
+    /// English draughts, or American checkers, on an 8x8 board
+    English,
+    /// Russian draughts on an 8x8 board, pawns going on as kings once promoted during a capture
+    Russian,
+    /// international rules played on an 8x8 board
+    Brazilian,
+    /// American pool checkers on an 8x8 board, with flying kings but no majority rule
+    Pool,
+    /// international rules played on a 12x12 board
+    Canadian,
+    /// Frisian draughts on a 10x10 board, pieces capturing along rows and columns too
+    Frisian,
+}
+
+/// Captures the side to move may choose from, capturing being mandatory in every game type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capture {
+    /// any capture
+    Free,
+    /// only the captures taking the most pieces
+    Majority,
+    /// only the captures of the highest value, a king counting a little less than two pawns.
+    /// Between captures of the same value a king has to capture rather than a pawn.
+    Value,
+}
+
+/// When a pawn reaching the last row during a capture gets promoted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Promotion {
+    /// only if the capture ends there, the pawn taking the next pieces as a pawn
+    AtTheEnd,
+    /// right away, the capture going on as a king
+    DuringCapture,
+}
+
+/// Board and moves of a game type
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rules {
+    /// number of cells along each side of the board
+    pub size: usize,
+    /// rows filled with pawns on each side in the initial position
+    pub rows: usize,
+    /// side making the first move
+    pub first: Color,
+    /// kings go any distance along a diagonal and capture distant pieces,
+    /// otherwise they step and jump like pawns do, in every direction
+    pub flying_kings: bool,
+    /// pawns capture backwards as well as forwards
+    pub pawns_capture_backwards: bool,
+    /// pieces capture along rows and columns as well as along diagonals,
+    /// jumping from one dark square to the next one two cells away
+    pub orthogonal_captures: bool,
+    pub capture: Capture,
+    pub promotion: Promotion,
+    /// plies played only with kings and without captures after which the game is drawn
+    pub king_moves: usize,
+    /// endgames against a lone king are drawn by the 16 and 5-move rules
+    pub endgame_rules: bool,
+}
+
+impl GameType {
+    pub fn rules(self) -> Rules {
+        match self {
+            GameType::International => Rules {
+                size: 10,
+                rows: 4,
+                first: Color::White,
+                flying_kings: true,
+                pawns_capture_backwards: true,
+                orthogonal_captures: false,
+                capture: Capture::Majority,
+                promotion: Promotion::AtTheEnd,
+                king_moves: 50,
+                endgame_rules: true,
+            },
+            GameType::English => Rules {
+                size: 8,
+                rows: 3,
+                first: Color::Black,
+                flying_kings: false,
+                pawns_capture_backwards: false,
+                orthogonal_captures: false,
+                capture: Capture::Free,
+                promotion: Promotion::AtTheEnd,
+                king_moves: 80,
+                endgame_rules: false,
+            },
+            GameType::Russian => Rules {
+                size: 8,
+                rows: 3,
+                first: Color::White,
+                flying_kings: true,
+                pawns_capture_backwards: true,
+                orthogonal_captures: false,
+                capture: Capture::Free,
+                promotion: Promotion::DuringCapture,
+                king_moves: 30,
+                endgame_rules: false,
+            },
+            GameType::Brazilian => Rules {
+                size: 8,
+                rows: 3,
+                ..GameType::International.rules()
+            },
+            GameType::Pool => Rules {
+                size: 8,
+                rows: 3,
+                first: Color::Black,
+                flying_kings: true,
+                pawns_capture_backwards: true,
+                orthogonal_captures: false,
+                capture: Capture::Free,
+                promotion: Promotion::AtTheEnd,
+                king_moves: 80,
+                endgame_rules: false,
+            },
+            GameType::Canadian => Rules {
+                size: 12,
+                rows: 5,
+                ..GameType::International.rules()
+            },
+            GameType::Frisian => Rules {
+                orthogonal_captures: true,
+                capture: Capture::Value,
+                endgame_rules: false,
+                ..GameType::International.rules()
+            },
+        }
+    }
+
+    /// returns the number of the game type in the `GameType` tag of PDN
+    pub fn pdn_number(self) -> u32 {
+        match self {
+            GameType::International => 20,
+            GameType::English => 21,
+            GameType::Pool => 23,
+            GameType::Russian => 25,
+            GameType::Brazilian => 26,
+            GameType::Canadian => 27,
+            GameType::Frisian => 40,
+        }
+    }
+
+    /// returns the game type numbered `number` in the `GameType` tag of PDN
+    pub fn from_pdn_number(number: u32) -> Option<Self> {
+        match number {
+            20 => Some(GameType::International),
+            21 => Some(GameType::English),
+            23 => Some(GameType::Pool),
+            25 => Some(GameType::Russian),
+            26 => Some(GameType::Brazilian),
+            27 => Some(GameType::Canadian),
+            40 => Some(GameType::Frisian),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+use crate::board::Board;
+
+#[test]
+fn test_pdn_numbers() {
+    let game_types = [
+        GameType::International,
+        GameType::English,
+        GameType::Russian,
+        GameType::Brazilian,
+        GameType::Pool,
+        GameType::Canadian,
+        GameType::Frisian,
+    ];
+    for &game_type in &game_types {
+        assert_eq!(
+            GameType::from_pdn_number(game_type.pdn_number()),
+            Some(game_type)
+        );
+        let board = Board::initial(game_type);
+        let json = serde_json::to_value(&board).unwrap();
+        assert_eq!(Board::deserialize_for(game_type, &json).unwrap(), board);
+    }
+    assert_eq!(GameType::from_pdn_number(22), None);
+    assert_eq!(
+        serde_json::from_str::<GameType>(r#""russian""#).unwrap(),
+        GameType::Russian
+    );
+}
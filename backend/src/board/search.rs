@@ -0,0 +1,1165 @@
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
+
+use crate::board::bitboard::{BitBoard, BitMove};
+use crate::board::book::OpeningBook;
+use crate::board::cell::Cell;
+use crate::board::evaluation::{Evaluator, Weights};
+use crate::board::piece::Color;
+use crate::board::rules::GameType;
+use crate::board::tablebase::{Outcome, Tablebase};
+use crate::board::transposition::{Bound, TableEntry, TranspositionTable};
+use crate::board::turn::Move;
+use crate::board::Board;
+
+/// score of a won position, shortened by the number of plies needed to win
+pub const WIN_SCORE: i32 = 1_000_000;
+
+/// scores beyond this one are wins or losses in a number of plies
+const WIN_THRESHOLD: i32 = WIN_SCORE - 10_000;
+/// half the width of the window around the previous score a deeper search starts with
+const ASPIRATION_WINDOW: i32 = 50;
+/// nodes searched between two looks at the clock, a power of two
+const CLOCK_INTERVAL: usize = 1024;
+/// moves the remaining clock time is shared between
+const MOVES_TO_GO: u32 = 30;
+
+/// Settings of the engine search
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchSettings {
+    /// how many plies the engine looks ahead, at most when it searches with a time control
+    pub depth: usize,
+    /// threads sharing the root moves
+    pub threads: Threads,
+    /// entries of the transposition table shared by the threads, `0` searches without one
+    pub table_size: usize,
+    /// time the engine may think for, `None` lets it reach `depth`
+    pub time: Option<TimeControl>,
+    /// book `Board::find_best_move` plays from before searching, scoring its moves `0`
+    pub book: Option<Arc<OpeningBook>>,
+    /// results of the positions with few pieces, looked up instead of searched
+    pub tablebase: Option<Arc<Tablebase>>,
+    /// plays on the captures left at the horizon, otherwise the position there is evaluated
+    /// as it stands, blind to the shots beyond the depth
+    pub quiescence: bool,
+}
+
+impl Default for SearchSettings {
+    fn default() -> Self {
+        Self {
+            depth: 6,
+            threads: Threads::Global,
+            table_size: 1 << 20,
+            time: None,
+            book: None,
+            tablebase: None,
+            quiescence: true,
+        }
+    }
+}
+
+/// Threads a search runs on
+#[derive(Debug, Clone)]
+pub enum Threads {
+    /// the calling thread alone, the search being fully deterministic
+    Single,
+    /// the global rayon pool, one thread per core
+    Global,
+    /// a pool built once with `Threads::pool` and shared by the searches
+    Pool(Arc<ThreadPool>),
+}
+
+impl Threads {
+    /// builds a pool of `threads` threads for the searches to share
+    pub fn pool(threads: usize) -> Result<Self, ThreadPoolBuildError> {
+        let pool = ThreadPoolBuilder::new().num_threads(threads).build()?;
+        Ok(Threads::Pool(Arc::new(pool)))
+    }
+}
+
+/// pools are the same only when they are the very same pool
+impl PartialEq for Threads {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Threads::Single, Threads::Single) | (Threads::Global, Threads::Global) => true,
+            (Threads::Pool(pool), Threads::Pool(other)) => Arc::ptr_eq(pool, other),
+            _ => false,
+        }
+    }
+}
+
+/// Time the engine may spend on a move
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeControl {
+    /// think for a fixed time
+    MoveTime(Duration),
+    /// the clock of the engine shows `remaining`, gaining `increment` after every move
+    Clock {
+        remaining: Duration,
+        increment: Duration,
+    },
+}
+
+impl TimeControl {
+    /// returns how long the engine may think about the current move
+    pub fn budget(&self) -> Duration {
+        match *self {
+            TimeControl::MoveTime(time) => time,
+            TimeControl::Clock {
+                remaining,
+                increment,
+            } => (remaining / MOVES_TO_GO + increment).min(remaining / 2),
+        }
+    }
+}
+
+/// Flag stopping a running search, shared with the threads that may raise it
+#[derive(Debug, Clone, Default)]
+pub struct StopFlag(Arc<AtomicBool>);
+
+impl StopFlag {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// asks the search to end and return the best move found so far
+    pub fn stop(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Representation of a position the engine can search
+pub trait SearchPosition: Clone + Send + Sync {
+    type Move: Clone + Send + Sync;
+
+    /// returns the legal moves of `color`
+    fn moves(&self, color: Color) -> Vec<Self::Move>;
+
+    /// returns the position after `turn` is played
+    fn after(&self, turn: &Self::Move) -> Self;
+
+    /// returns `true` if `turn` takes pieces
+    fn is_capture(&self, turn: &Self::Move) -> bool;
+
+    /// returns the outcome of the position with `color` to move found in `tablebase`
+    fn probe(&self, tablebase: &Tablebase, color: Color) -> Option<Outcome>;
+
+    /// returns the hash of the position with `color` to move
+    fn hash(&self, color: Color) -> u64;
+}
+
+impl SearchPosition for Board {
+    type Move = Move;
+
+    fn moves(&self, color: Color) -> Vec<Move> {
+        self.legal_moves(color)
+    }
+
+    fn after(&self, turn: &Move) -> Self {
+        let mut board = self.clone();
+        board.apply(turn);
+        board
+    }
+
+    fn is_capture(&self, turn: &Move) -> bool {
+        !turn.kills.is_empty()
+    }
+
+    /// the tablebases know only international positions
+    fn probe(&self, tablebase: &Tablebase, color: Color) -> Option<Outcome> {
+        let pieces = self.iter().filter(|piece| !piece.is_empty()).count();
+        if self.game_type() != GameType::International || pieces > tablebase.pieces() {
+            return None;
+        }
+        tablebase.probe(&BitBoard::from(self), color)
+    }
+
+    fn hash(&self, color: Color) -> u64 {
+        self.position_hash(color)
+    }
+}
+
+impl SearchPosition for BitBoard {
+    type Move = BitMove;
+
+    fn moves(&self, color: Color) -> Vec<BitMove> {
+        self.legal_moves(color)
+    }
+
+    fn after(&self, turn: &BitMove) -> Self {
+        let mut bits = *self;
+        bits.apply(turn);
+        bits
+    }
+
+    fn is_capture(&self, turn: &BitMove) -> bool {
+        turn.captured != 0
+    }
+
+    fn probe(&self, tablebase: &Tablebase, color: Color) -> Option<Outcome> {
+        tablebase.probe(self, color)
+    }
+
+    fn hash(&self, color: Color) -> u64 {
+        BitBoard::hash(self, color)
+    }
+}
+
+impl Board {
+    /// returns the best move of `color` together with its score, seen from the `color` side.
+    /// Returns `None` if `color` has no legal moves.
+    ///
+    /// Positions found in the book of `settings` are not searched, one of the book moves being
+    /// picked at random. International positions are searched on their `BitBoard`,
+    /// those of other game types on the board itself, evaluated with the default `Weights`.
+    pub fn find_best_move(&self, color: Color, settings: &SearchSettings) -> Option<(Move, i32)> {
+        self.find_best_move_with(color, settings, &Weights::default())
+    }
+
+    /// returns the best move of `color` together with its score, like `find_best_move`,
+    /// scoring the positions with `evaluator`
+    pub fn find_best_move_with<E>(
+        &self,
+        color: Color,
+        settings: &SearchSettings,
+        evaluator: &E,
+    ) -> Option<(Move, i32)>
+    where
+        E: Evaluator<BitBoard> + Evaluator<Board>,
+    {
+        self.find_best_move_until(color, settings, evaluator, &StopFlag::new())
+    }
+
+    /// returns the best move of `color` together with its score, like `find_best_move_with`,
+    /// giving the best move found so far once `stop` is raised
+    pub fn find_best_move_until<E>(
+        &self,
+        color: Color,
+        settings: &SearchSettings,
+        evaluator: &E,
+        stop: &StopFlag,
+    ) -> Option<(Move, i32)>
+    where
+        E: Evaluator<BitBoard> + Evaluator<Board>,
+    {
+        let book = settings.book.as_ref();
+        if let Some(turn) = book.and_then(|book| book.pick(self, color, &mut rand::thread_rng())) {
+            return Some((turn, 0));
+        }
+        if self.game_type() != GameType::International {
+            return search_until(self, color, settings, evaluator, stop);
+        }
+
+        let bits = BitBoard::from(self);
+        let (best, score) = search_until(&bits, color, settings, evaluator, stop)?;
+        let turn = self
+            .legal_moves(color)
+            .into_iter()
+            .find(|turn| best.matches(turn))
+            .expect("both boards have the same legal moves");
+        Some((turn, score))
+    }
+}
+
+/// returns the best move of `color` in `position` together with its score,
+/// seen from the `color` side, the positions being scored by `evaluator`.
+/// Returns `None` if `color` has no legal moves.
+pub fn search<P, E>(
+    position: &P,
+    color: Color,
+    settings: &SearchSettings,
+    evaluator: &E,
+) -> Option<(P::Move, i32)>
+where
+    P: SearchPosition,
+    E: Evaluator<P>,
+{
+    search_until(position, color, settings, evaluator, &StopFlag::new())
+}
+
+/// returns the best move of `color` in `position` together with its score, like `search`,
+/// ending early once `stop` is raised.
+///
+/// The search deepens one ply at a time up to `settings.depth`, or for as long as the time
+/// control allows. When stopped it returns the result of the deepest search it completed;
+/// the search one ply deep always completes.
+pub fn search_until<P, E>(
+    position: &P,
+    color: Color,
+    settings: &SearchSettings,
+    evaluator: &E,
+    stop: &StopFlag,
+) -> Option<(P::Move, i32)>
+where
+    P: SearchPosition,
+    E: Evaluator<P>,
+{
+    let mut moves = position.moves(color);
+    if moves.is_empty() {
+        return None;
+    }
+
+    let started = Instant::now();
+    let budget = settings.time.map(|time| time.budget());
+    let table = TranspositionTable::new(settings.table_size);
+    let limits = Limits {
+        stop,
+        deadline: budget.map(|budget| started + budget),
+        nodes: AtomicUsize::new(0),
+        timed_out: AtomicBool::new(false),
+    };
+    let mut best: Option<i32> = None;
+    for depth in 1..=settings.depth.max(1) {
+        let context = Context {
+            table: &table,
+            evaluator,
+            limits: if depth > 1 { Some(&limits) } else { None },
+            tablebase: settings.tablebase.as_deref(),
+            quiescence: settings.quiescence,
+        };
+        let search_window = |alpha, beta| match &settings.threads {
+            Threads::Single => search_root(position, color, &moves, depth, alpha, beta, &context),
+            Threads::Global => {
+                search_root_parallel(position, color, &moves, depth, alpha, beta, &context)
+            }
+            Threads::Pool(pool) => pool.install(|| {
+                search_root_parallel(position, color, &moves, depth, alpha, beta, &context)
+            }),
+        };
+
+        // a score outside the window around the previous one is only a bound,
+        // the moves are searched again with the full window
+        let (alpha, beta) = match best {
+            Some(score) if score.abs() < WIN_THRESHOLD => {
+                (score - ASPIRATION_WINDOW, score + ASPIRATION_WINDOW)
+            }
+            _ => (-WIN_SCORE - 1, WIN_SCORE + 1),
+        };
+        let mut result =
+            search_window(alpha, beta).filter(|&(_, score)| score > alpha && score < beta);
+        if result.is_none() && !context.stopped() {
+            result = search_window(-WIN_SCORE - 1, WIN_SCORE + 1);
+        }
+        if context.stopped() {
+            break;
+        }
+
+        let (index, score) = result.expect("a full window search finds a move");
+        moves[..=index].rotate_right(1);
+        best = Some(score);
+
+        let half_spent = matches!(budget, Some(budget) if started.elapsed() * 2 >= budget);
+        if half_spent || stop.is_stopped() {
+            break;
+        }
+    }
+
+    let score = best.expect("the search one ply deep always completes");
+    Some((moves.swap_remove(0), score))
+}
+
+/// returns every legal move of `color` in `position` with its exact score, seen from the
+/// `color` side and searched `settings.depth` plies deep on the calling thread.
+/// The time control of `settings` is not used.
+pub fn score_moves<P, E>(
+    position: &P,
+    color: Color,
+    settings: &SearchSettings,
+    evaluator: &E,
+) -> Vec<(P::Move, i32)>
+where
+    P: SearchPosition,
+    E: Evaluator<P>,
+{
+    let depth = settings.depth.max(1);
+    let table = TranspositionTable::new(settings.table_size);
+    let context = Context {
+        table: &table,
+        evaluator,
+        limits: None,
+        tablebase: settings.tablebase.as_deref(),
+        quiescence: settings.quiescence,
+    };
+    position
+        .moves(color)
+        .into_iter()
+        .map(|turn| {
+            let score = search_move(
+                position,
+                &turn,
+                color,
+                depth,
+                -WIN_SCORE - 1,
+                WIN_SCORE + 1,
+                &context,
+            );
+            (turn, score)
+        })
+        .collect()
+}
+
+/// State shared by every node of a search
+struct Context<'a, E> {
+    table: &'a TranspositionTable,
+    evaluator: &'a E,
+    /// limits of the search, `None` when it must complete
+    limits: Option<&'a Limits<'a>>,
+    tablebase: Option<&'a Tablebase>,
+    quiescence: bool,
+}
+
+impl<E> Context<'_, E> {
+    /// returns `true` once the search must end, checking the clock every few nodes
+    fn stopped(&self) -> bool {
+        match self.limits {
+            Some(limits) => limits.reached(),
+            None => false,
+        }
+    }
+}
+
+/// Limits ending a search before it reaches its depth
+struct Limits<'a> {
+    stop: &'a StopFlag,
+    deadline: Option<Instant>,
+    /// nodes visited, the clock being read once every `CLOCK_INTERVAL` of them
+    nodes: AtomicUsize,
+    timed_out: AtomicBool,
+}
+
+impl Limits<'_> {
+    fn reached(&self) -> bool {
+        if self.stop.is_stopped() || self.timed_out.load(Ordering::Relaxed) {
+            return true;
+        }
+        if let Some(deadline) = self.deadline {
+            let nodes = self.nodes.fetch_add(1, Ordering::Relaxed);
+            if nodes & (CLOCK_INTERVAL - 1) == 0 && Instant::now() >= deadline {
+                self.timed_out.store(true, Ordering::Relaxed);
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// searches the root moves one by one on the current thread, returns the index of the best
+/// move and its score if it is above `alpha`
+fn search_root<P, E>(
+    position: &P,
+    color: Color,
+    moves: &[P::Move],
+    depth: usize,
+    mut alpha: i32,
+    beta: i32,
+    context: &Context<'_, E>,
+) -> Option<(usize, i32)>
+where
+    P: SearchPosition,
+    E: Evaluator<P>,
+{
+    let mut best = None;
+
+    for (index, turn) in moves.iter().enumerate() {
+        let score = search_move(position, turn, color, depth, alpha, beta, context);
+        if context.stopped() {
+            return best;
+        }
+        if score > alpha {
+            alpha = score;
+            best = Some((index, score));
+        }
+        if score >= beta {
+            break;
+        }
+    }
+
+    best
+}
+
+/// searches the first root move alone, then splits the remaining ones between the threads
+/// of the current pool. The threads share the best score found so far as their lower bound.
+///
+/// Among moves of equal score the earliest one wins, but which of them gets an exact score
+/// depends on timing, so the returned move may differ between runs; the score does not.
+fn search_root_parallel<P, E>(
+    position: &P,
+    color: Color,
+    moves: &[P::Move],
+    depth: usize,
+    alpha: i32,
+    beta: i32,
+    context: &Context<'_, E>,
+) -> Option<(usize, i32)>
+where
+    P: SearchPosition,
+    E: Evaluator<P>,
+{
+    let (first, others) = moves.split_first()?;
+    let first_score = search_move(position, first, color, depth, alpha, beta, context);
+    if context.stopped() {
+        return None;
+    }
+    if first_score >= beta {
+        return Some((0, first_score));
+    }
+    let bound = AtomicI32::new(first_score.max(alpha));
+
+    let others: Vec<(usize, i32)> = others
+        .par_iter()
+        .enumerate()
+        .filter_map(|(index, turn)| {
+            let bound_now = bound.load(Ordering::Relaxed);
+            if bound_now >= beta {
+                return None;
+            }
+            let score = search_move(position, turn, color, depth, bound_now, beta, context);
+            if score > bound_now && !context.stopped() {
+                bound.fetch_max(score, Ordering::Relaxed);
+                Some((index + 1, score))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    let mut best = Some((0, first_score)).filter(|_| first_score > alpha);
+    for (index, score) in others {
+        match best {
+            Some((_, best_score)) if best_score >= score => {}
+            _ => best = Some((index, score)),
+        }
+    }
+    best
+}
+
+/// returns the score of `turn` played in `position` from the `color` side
+fn search_move<P, E>(
+    position: &P,
+    turn: &P::Move,
+    color: Color,
+    depth: usize,
+    alpha: i32,
+    beta: i32,
+    context: &Context<'_, E>,
+) -> i32
+where
+    P: SearchPosition,
+    E: Evaluator<P>,
+{
+    let next = position.after(turn);
+    -negamax(
+        &next,
+        color.opposite(),
+        depth - 1,
+        -beta,
+        -alpha,
+        1,
+        context,
+    )
+}
+
+/// fail-hard negamax with alpha-beta pruning, returns the score seen from the `color` side.
+///
+/// Scores found in `table` are reused only when searched to the same depth, which keeps them
+/// equal to those of the plain search whichever thread stored them. Best moves of any depth
+/// are tried first.
+///
+/// Once the search is stopped the scores mean nothing and are not stored.
+fn negamax<P, E>(
+    position: &P,
+    color: Color,
+    depth: usize,
+    mut alpha: i32,
+    beta: i32,
+    ply: i32,
+    context: &Context<'_, E>,
+) -> i32
+where
+    P: SearchPosition,
+    E: Evaluator<P>,
+{
+    if depth == 0 {
+        return quiesce(position, color, alpha, beta, ply, context);
+    }
+    if let Some(score) = probe(position, color, alpha, beta, ply, context) {
+        return score;
+    }
+    if context.stopped() {
+        return alpha;
+    }
+
+    let hash = position.hash(color);
+    let stored = context.table.probe(hash);
+    if let Some(entry) = stored.filter(|entry| entry.depth == depth) {
+        let score = from_table(entry.score, ply);
+        match entry.bound {
+            Bound::Exact => return score.max(alpha).min(beta),
+            Bound::Lower if score >= beta => return beta,
+            Bound::Upper if score <= alpha => return alpha,
+            _ => {}
+        }
+    }
+
+    let moves = position.moves(color);
+    if moves.is_empty() {
+        return -WIN_SCORE + ply;
+    }
+
+    let mut order: Vec<usize> = (0..moves.len()).collect();
+    if let Some(best) = stored.and_then(|entry| entry.best_move) {
+        if best < moves.len() {
+            order.swap(0, best);
+        }
+    }
+
+    let mut best_move = None;
+    for index in order {
+        let next = position.after(&moves[index]);
+        let score = -negamax(
+            &next,
+            color.opposite(),
+            depth - 1,
+            -beta,
+            -alpha,
+            ply + 1,
+            context,
+        );
+        if context.stopped() {
+            return alpha;
+        }
+
+        if score >= beta {
+            let entry = TableEntry {
+                depth,
+                bound: Bound::Lower,
+                score: to_table(beta, ply),
+                best_move: Some(index),
+            };
+            context.table.store(hash, entry);
+            return beta;
+        }
+        if score > alpha {
+            alpha = score;
+            best_move = Some(index);
+        }
+    }
+
+    let entry = TableEntry {
+        depth,
+        bound: if best_move.is_some() {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        },
+        score: to_table(alpha, ply),
+        best_move,
+    };
+    context.table.store(hash, entry);
+    alpha
+}
+
+/// plays on the captures left at the horizon, which being mandatory can not be declined,
+/// and evaluates the first position without one, or the position as it stands when
+/// the search runs without quiescence. Returns fail-hard scores like `negamax`,
+/// positions the tablebase knows being scored by it.
+fn quiesce<P, E>(
+    position: &P,
+    color: Color,
+    mut alpha: i32,
+    beta: i32,
+    ply: i32,
+    context: &Context<'_, E>,
+) -> i32
+where
+    P: SearchPosition,
+    E: Evaluator<P>,
+{
+    if let Some(score) = probe(position, color, alpha, beta, ply, context) {
+        return score;
+    }
+    if context.stopped() {
+        return alpha;
+    }
+
+    let moves = position.moves(color);
+    match moves.first() {
+        None => return (-WIN_SCORE + ply).max(alpha).min(beta),
+        Some(turn) if !context.quiescence || !position.is_capture(turn) => {
+            let score = context.evaluator.evaluate(position, color);
+            return score.max(alpha).min(beta);
+        }
+        _ => {}
+    }
+
+    for turn in &moves {
+        let next = position.after(turn);
+        let score = -quiesce(&next, color.opposite(), -beta, -alpha, ply + 1, context);
+        if context.stopped() {
+            return alpha;
+        }
+        if score >= beta {
+            return beta;
+        }
+        alpha = alpha.max(score);
+    }
+    alpha
+}
+
+/// returns the score the tablebase gives the position, `None` if it does not know it
+fn probe<P, E>(
+    position: &P,
+    color: Color,
+    alpha: i32,
+    beta: i32,
+    ply: i32,
+    context: &Context<'_, E>,
+) -> Option<i32>
+where
+    P: SearchPosition,
+{
+    let outcome = position.probe(context.tablebase?, color)?;
+    Some(tablebase_score(outcome, ply).max(alpha).min(beta))
+}
+
+/// returns the score of a position `ply` plies from the root with given `outcome`
+fn tablebase_score(outcome: Outcome, ply: i32) -> i32 {
+    match outcome {
+        Outcome::Win { plies } => WIN_SCORE - ply - i32::from(plies),
+        Outcome::Loss { plies } => -WIN_SCORE + ply + i32::from(plies),
+        Outcome::Draw => 0,
+    }
+}
+
+/// turns a win or loss counted in plies from the root into one counted from the position,
+/// so it stays right when the position is reached by another path
+fn to_table(score: i32, ply: i32) -> i32 {
+    if score > WIN_THRESHOLD {
+        score + ply
+    } else if score < -WIN_THRESHOLD {
+        score - ply
+    } else {
+        score
+    }
+}
+
+/// turns a score read from the table back into one counted from the root
+fn from_table(score: i32, ply: i32) -> i32 {
+    if score > WIN_THRESHOLD {
+        score - ply
+    } else if score < -WIN_THRESHOLD {
+        score + ply
+    } else {
+        score
+    }
+}
+
+#[cfg(test)]
+use crate::board::{board_with, piece::Piece, position::Position};
+
+/// returns a position with a king and a few pawns on each side, still far from the end
+#[cfg(test)]
+fn middle_game() -> Board {
+    board_with(&[
+        ((1, 8), Piece::WhitePawn),
+        ((3, 8), Piece::WhitePawn),
+        ((4, 7), Piece::WhitePawn),
+        ((6, 7), Piece::WhitePawn),
+        ((9, 6), Piece::WhiteQueen),
+        ((2, 3), Piece::BlackPawn),
+        ((4, 3), Piece::BlackPawn),
+        ((5, 2), Piece::BlackPawn),
+        ((7, 4), Piece::BlackPawn),
+        ((8, 1), Piece::BlackQueen),
+    ])
+}
+
+#[test]
+fn test_no_moves() {
+    let board = board_with(&[((0, 9), Piece::BlackPawn)]);
+    assert_eq!(
+        board.find_best_move(Color::White, &SearchSettings::default()),
+        None
+    );
+}
+
+#[test]
+fn test_does_not_hang_a_piece() {
+    let board = board_with(&[((4, 5), Piece::WhitePawn), ((2, 3), Piece::BlackPawn)]);
+    let settings = SearchSettings {
+        depth: 2,
+        threads: Threads::Single,
+        ..SearchSettings::default()
+    };
+    let (turn, score) = board
+        .find_best_move_with(Color::White, &settings, &Weights::material())
+        .unwrap();
+    assert_eq!(turn.end_position, Position((5, 4)));
+    assert_eq!(score, 0);
+}
+
+#[test]
+fn test_finds_the_win() {
+    let board = board_with(&[((0, 9), Piece::WhiteQueen), ((4, 5), Piece::BlackPawn)]);
+    let settings = SearchSettings {
+        depth: 3,
+        threads: Threads::Single,
+        ..SearchSettings::default()
+    };
+    let (turn, score) = board.find_best_move(Color::White, &settings).unwrap();
+    assert_eq!(turn.kills, vec![Position((4, 5))]);
+    assert_eq!(score, WIN_SCORE - 1);
+}
+
+#[test]
+fn test_initial_position() {
+    let board = Board::new();
+    let settings = SearchSettings {
+        depth: 4,
+        threads: Threads::Single,
+        ..SearchSettings::default()
+    };
+    let (turn, score) = board
+        .find_best_move_with(Color::White, &settings, &Weights::material())
+        .unwrap();
+    assert!(board
+        .legal_moves(Color::White)
+        .iter()
+        .any(|legal| legal.is_equivalent(&turn)));
+    assert_eq!(score, 0);
+}
+
+#[test]
+fn test_parallel_search_agrees() {
+    let board = middle_game();
+    for &color in &[Color::White, Color::Black] {
+        let sequential = SearchSettings {
+            depth: 5,
+            threads: Threads::Single,
+            ..SearchSettings::default()
+        };
+        let parallel = SearchSettings {
+            depth: 5,
+            threads: Threads::pool(4).unwrap(),
+            ..SearchSettings::default()
+        };
+        let (_, expected) = board.find_best_move(color, &sequential).unwrap();
+        let (turn, score) = board.find_best_move(color, &parallel).unwrap();
+        assert_eq!(score, expected);
+        assert!(board
+            .legal_moves(color)
+            .iter()
+            .any(|legal| legal.is_equivalent(&turn)));
+    }
+}
+
+#[test]
+fn test_table_keeps_the_scores() {
+    let board = middle_game();
+    for &color in &[Color::White, Color::Black] {
+        let plain = SearchSettings {
+            depth: 5,
+            threads: Threads::Single,
+            table_size: 0,
+            time: None,
+            book: None,
+            tablebase: None,
+            quiescence: true,
+        };
+        let with_table = SearchSettings {
+            table_size: 1 << 16,
+            ..plain.clone()
+        };
+        let (expected_move, expected) = board.find_best_move(color, &plain).unwrap();
+        let (turn, score) = board.find_best_move(color, &with_table).unwrap();
+        assert_eq!(score, expected);
+        assert!(turn.is_equivalent(&expected_move));
+    }
+}
+
+#[test]
+fn test_mate_scores_in_the_table() {
+    assert_eq!(to_table(WIN_SCORE - 5, 3), WIN_SCORE - 2);
+    assert_eq!(from_table(WIN_SCORE - 2, 1), WIN_SCORE - 3);
+    assert_eq!(to_table(-WIN_SCORE + 5, 3), -WIN_SCORE + 2);
+    assert_eq!(from_table(-WIN_SCORE + 2, 1), -WIN_SCORE + 3);
+    assert_eq!(to_table(250, 3), 250);
+}
+
+#[test]
+fn test_both_boards_agree() {
+    let board = middle_game();
+    let bits = BitBoard::from(&board);
+    let weights = Weights::default();
+    let settings = SearchSettings {
+        depth: 4,
+        threads: Threads::Single,
+        ..SearchSettings::default()
+    };
+    for &color in &[Color::White, Color::Black] {
+        let (_, matrix) = search(&board, color, &settings, &weights).unwrap();
+        let (_, bitboard) = search(&bits, color, &settings, &weights).unwrap();
+        assert_eq!(matrix, bitboard);
+    }
+}
+
+#[test]
+fn test_time_budget() {
+    let second = Duration::from_secs(1);
+    assert_eq!(TimeControl::MoveTime(second).budget(), second);
+
+    let clock = TimeControl::Clock {
+        remaining: second * 60,
+        increment: second,
+    };
+    assert_eq!(clock.budget(), second * 3);
+
+    let short = TimeControl::Clock {
+        remaining: second,
+        increment: second * 2,
+    };
+    assert_eq!(short.budget(), second / 2);
+}
+
+#[test]
+fn test_stopped_search_returns_a_move() {
+    let board = Board::new();
+    let settings = SearchSettings {
+        depth: 100,
+        threads: Threads::Single,
+        ..SearchSettings::default()
+    };
+    let stop = StopFlag::new();
+    stop.stop();
+    let (turn, _) = board
+        .find_best_move_until(Color::White, &settings, &Weights::default(), &stop)
+        .unwrap();
+    assert!(board
+        .legal_moves(Color::White)
+        .iter()
+        .any(|legal| legal.is_equivalent(&turn)));
+}
+
+#[test]
+fn test_stop_from_another_thread() {
+    let board = Board::new();
+    let settings = SearchSettings {
+        depth: 100,
+        threads: Threads::pool(2).unwrap(),
+        ..SearchSettings::default()
+    };
+    let stop = StopFlag::new();
+    let handle = {
+        let stop = stop.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            stop.stop();
+        })
+    };
+    let (turn, _) = board
+        .find_best_move_until(Color::White, &settings, &Weights::default(), &stop)
+        .unwrap();
+    handle.join().unwrap();
+    assert!(board
+        .legal_moves(Color::White)
+        .iter()
+        .any(|legal| legal.is_equivalent(&turn)));
+}
+
+#[test]
+fn test_move_time() {
+    let board = Board::new();
+    let settings = SearchSettings {
+        depth: 100,
+        threads: Threads::Single,
+        time: Some(TimeControl::MoveTime(Duration::from_millis(100))),
+        ..SearchSettings::default()
+    };
+    let started = Instant::now();
+    assert!(board.find_best_move(Color::White, &settings).is_some());
+    assert!(started.elapsed() < Duration::from_secs(2));
+}
+
+#[test]
+fn test_deepening_keeps_the_scores() {
+    let board = middle_game();
+    let bits = BitBoard::from(&board);
+    let weights = Weights::default();
+    let context = Context {
+        table: &TranspositionTable::new(0),
+        evaluator: &weights,
+        limits: None,
+        tablebase: None,
+        quiescence: true,
+    };
+    for &color in &[Color::White, Color::Black] {
+        let moves = bits.moves(color);
+        let settings = SearchSettings {
+            depth: 5,
+            threads: Threads::Single,
+            table_size: 0,
+            time: None,
+            book: None,
+            tablebase: None,
+            quiescence: true,
+        };
+        let (_, expected) = search_root(
+            &bits,
+            color,
+            &moves,
+            5,
+            -WIN_SCORE - 1,
+            WIN_SCORE + 1,
+            &context,
+        )
+        .unwrap();
+        let (_, score) = search(&bits, color, &settings, &weights).unwrap();
+        assert_eq!(score, expected);
+    }
+}
+
+#[test]
+fn test_quiescence() {
+    // black takes the pawn on 28 right after the horizon
+    let board = board_with(&[
+        ((4, 5), Piece::WhitePawn),
+        ((9, 8), Piece::WhitePawn),
+        ((3, 4), Piece::BlackPawn),
+    ]);
+    let weights = Weights::material();
+    let context = Context {
+        table: &TranspositionTable::new(0),
+        evaluator: &weights,
+        limits: None,
+        tablebase: None,
+        quiescence: true,
+    };
+    let (alpha, beta) = (-WIN_SCORE - 1, WIN_SCORE + 1);
+    assert_eq!(weights.evaluate(&board, Color::Black), -100);
+    assert_eq!(
+        negamax(&board, Color::Black, 0, alpha, beta, 1, &context),
+        0
+    );
+
+    // the pawn on 33 does not step to 28, next to the black one
+    let board = board_with(&[
+        ((5, 6), Piece::WhitePawn),
+        ((9, 8), Piece::WhitePawn),
+        ((3, 4), Piece::BlackPawn),
+    ]);
+    let settings = SearchSettings {
+        depth: 1,
+        threads: Threads::Single,
+        ..SearchSettings::default()
+    };
+    let (turn, score) = board
+        .find_best_move_with(Color::White, &settings, &weights)
+        .unwrap();
+    assert_ne!(turn.end_position, Position((4, 5)));
+    assert_eq!(score, 100);
+}
+
+#[test]
+fn test_quiescence_stops_and_probes() {
+    // black takes the pawn on 28, leaving its pawn alone against the white king
+    let board = board_with(&[
+        ((4, 5), Piece::WhitePawn),
+        ((0, 9), Piece::WhiteQueen),
+        ((3, 4), Piece::BlackPawn),
+    ]);
+    let weights = Weights::material();
+    let table = TranspositionTable::new(0);
+    let stop = StopFlag::new();
+    stop.stop();
+    let limits = Limits {
+        stop: &stop,
+        deadline: None,
+        nodes: AtomicUsize::new(0),
+        timed_out: AtomicBool::new(false),
+    };
+    let context = Context {
+        table: &table,
+        evaluator: &weights,
+        limits: Some(&limits),
+        tablebase: None,
+        quiescence: true,
+    };
+    assert_eq!(quiesce(&board, Color::Black, -5, 5, 1, &context), -5);
+
+    let tablebase = Tablebase::generate(2);
+    let capture = board.legal_moves(Color::Black).remove(0);
+    let after = board.after(&capture);
+    let outcome = after.probe(&tablebase, Color::White).unwrap();
+    let expected = -tablebase_score(outcome, 2);
+    let (alpha, beta) = (-WIN_SCORE - 1, WIN_SCORE + 1);
+    let context = Context {
+        table: &table,
+        evaluator: &weights,
+        limits: None,
+        tablebase: Some(&tablebase),
+        quiescence: true,
+    };
+    assert_eq!(
+        quiesce(&board, Color::Black, alpha, beta, 1, &context),
+        expected
+    );
+    let context = Context {
+        tablebase: None,
+        ..context
+    };
+    assert_ne!(
+        quiesce(&board, Color::Black, alpha, beta, 1, &context),
+        expected
+    );
+}
+
+#[test]
+fn test_score_moves() {
+    let board = board_with(&[
+        ((5, 6), Piece::WhitePawn),
+        ((9, 8), Piece::WhitePawn),
+        ((3, 4), Piece::BlackPawn),
+    ]);
+    let settings = SearchSettings {
+        depth: 3,
+        threads: Threads::Single,
+        ..SearchSettings::default()
+    };
+    let weights = Weights::material();
+    let scores = score_moves(&board, Color::White, &settings, &weights);
+    assert_eq!(scores.len(), board.legal_moves(Color::White).len());
+
+    let (_, best) = search(&board, Color::White, &settings, &weights).unwrap();
+    assert_eq!(scores.iter().map(|(_, score)| *score).max(), Some(best));
+}
+
+#[test]
+fn test_book_moves() {
+    let board = Board::new();
+    let mut book = OpeningBook::new();
+    book.add(board.position_hash(Color::White), "35-30", 1);
+    let settings = SearchSettings {
+        depth: 2,
+        threads: Threads::Single,
+        book: Some(Arc::new(book)),
+        ..SearchSettings::default()
+    };
+    let (turn, score) = board.find_best_move(Color::White, &settings).unwrap();
+    assert_eq!(turn.notation(10), "35-30");
+    assert_eq!(score, 0);
+
+    let (board, color) = Board::from_fen("B:W30,31-34,36-50:B1-20").unwrap();
+    let (turn, _) = board.find_best_move(color, &settings).unwrap();
+    assert!(board
+        .legal_moves(color)
+        .iter()
+        .any(|legal| legal.is_equivalent(&turn)));
+}
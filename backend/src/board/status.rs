@@ -0,0 +1,360 @@
+use serde::Serialize;
+
+use crate::board::cell::Cell;
+use crate::board::piece::Color;
+use crate::board::turn::Undo;
+use crate::board::Board;
+
+/// plies of the 16-move endgame rule
+const SIXTEEN_MOVES: usize = 32;
+/// plies of the 5-move endgame rule
+const FIVE_MOVES: usize = 10;
+
+/// State of a game, serialized as `{"state": "ongoing"}`, `{"state": "won", "winner": "white"}`
+/// or `{"state": "drawn", "reason": "repetition"}`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum GameStatus {
+    Ongoing,
+    /// the opponent of `winner` has no pieces or no legal move left
+    Won {
+        winner: Color,
+    },
+    Drawn {
+        reason: DrawReason,
+    },
+}
+
+/// Rule the game was drawn by
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DrawReason {
+    /// the same position, with the same side to move, occurred for the third time
+    Repetition,
+    /// both sides made 25 moves in a row with kings only, without capturing,
+    /// or as many as the rules of the game type allow
+    KingMoves,
+    /// both sides made 16 moves with three pieces, a king among them, against a lone king
+    SixteenMoves,
+    /// both sides made 5 moves with at most two pieces, a king among them, against a lone king
+    FiveMoves,
+}
+
+impl GameStatus {
+    pub fn is_over(&self) -> bool {
+        *self != GameStatus::Ongoing
+    }
+}
+
+impl Board {
+    /// returns `Won` when `turn`, the side to move, has no legal move left
+    /// (which includes having no pieces), `Ongoing` otherwise.
+    /// Draws depend on the history of the game, see `DrawTracker`.
+    pub fn status(&self, turn: Color) -> GameStatus {
+        if self.legal_moves(turn).is_empty() {
+            GameStatus::Won {
+                winner: turn.opposite(),
+            }
+        } else {
+            GameStatus::Ongoing
+        }
+    }
+
+    /// returns the number of pawns and kings of `color`
+    fn material(&self, color: Color) -> (usize, usize) {
+        self.iter()
+            .filter(|piece| piece.color() == color)
+            .fold((0, 0), |(pawns, kings), piece| {
+                if piece.is_pawn() {
+                    (pawns + 1, kings)
+                } else {
+                    (pawns, kings + 1)
+                }
+            })
+    }
+}
+
+/// Pawns and kings of white, then of black
+type Material = ((usize, usize), (usize, usize));
+
+/// Part of the game history looked at by the draw rules
+#[derive(Debug, Clone)]
+pub struct DrawTracker {
+    /// hashes of the positions since the last pawn move or capture, the current one last
+    positions: Vec<u64>,
+    /// plies in a row in which a king moved without capturing
+    king_moves: usize,
+    /// plies of king moves drawing the game
+    king_moves_limit: usize,
+    /// material of an endgame ruled by the 16 or 5-move rule, with plies played in it
+    endgame: Option<(Material, usize)>,
+}
+
+impl DrawTracker {
+    /// starts tracking the game at `board` with `turn` to move
+    pub fn new(board: &Board, turn: Color) -> Self {
+        Self {
+            positions: vec![board.position_hash(turn)],
+            king_moves: 0,
+            king_moves_limit: board.rules().king_moves,
+            endgame: DrawTracker::endgame(board).map(|material| (material, 0)),
+        }
+    }
+
+    /// notes the move `undo` was returned for, which left `board` with `turn` to move
+    pub fn record(&mut self, board: &Board, turn: Color, undo: &Undo) {
+        if undo.piece.is_pawn() || !undo.captured.is_empty() {
+            self.positions.clear();
+            self.king_moves = 0;
+        } else {
+            self.king_moves += 1;
+        }
+        self.positions.push(board.position_hash(turn));
+
+        let endgame = DrawTracker::endgame(board);
+        self.endgame = match (self.endgame, endgame) {
+            (Some((previous, plies)), Some(material)) if previous == material => {
+                Some((material, plies + 1))
+            }
+            (_, material) => material.map(|material| (material, 0)),
+        };
+    }
+
+    /// returns the rule drawing the game in the current position
+    pub fn draw(&self) -> Option<DrawReason> {
+        if let Some((material, plies)) = self.endgame {
+            let (limit, reason) = endgame_rule(material).expect("tracked endgames have a rule");
+            if plies >= limit {
+                return Some(reason);
+            }
+        }
+
+        let current = self
+            .positions
+            .last()
+            .expect("the current position is tracked");
+        let repeated = self
+            .positions
+            .iter()
+            .filter(|&hash| hash == current)
+            .count();
+        if repeated >= 3 {
+            return Some(DrawReason::Repetition);
+        }
+
+        if self.king_moves >= self.king_moves_limit {
+            return Some(DrawReason::KingMoves);
+        }
+        None
+    }
+
+    /// returns the status of the game in the current position, `board` with `turn` to move
+    pub fn status(&self, board: &Board, turn: Color) -> GameStatus {
+        match board.status(turn) {
+            GameStatus::Ongoing => self
+                .draw()
+                .map_or(GameStatus::Ongoing, |reason| GameStatus::Drawn { reason }),
+            won => won,
+        }
+    }
+
+    /// returns the material on `board` if an endgame rule applies to it
+    fn endgame(board: &Board) -> Option<Material> {
+        if !board.rules().endgame_rules {
+            return None;
+        }
+        let material = (board.material(Color::White), board.material(Color::Black));
+        endgame_rule(material).map(|_| material)
+    }
+}
+
+/// returns the plies allowed in an endgame of a lone king against some pieces,
+/// and the rule drawing it afterwards
+fn endgame_rule((white, black): Material) -> Option<(usize, DrawReason)> {
+    let lone_king = (0, 1);
+    let (pawns, kings) = match (white, black) {
+        (white, black) if white == lone_king => black,
+        (white, black) if black == lone_king => white,
+        _ => return None,
+    };
+    match pawns + kings {
+        _ if kings == 0 => None,
+        1 | 2 => Some((FIVE_MOVES, DrawReason::FiveMoves)),
+        3 => Some((SIXTEEN_MOVES, DrawReason::SixteenMoves)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+use crate::board::board_with;
+#[cfg(test)]
+use crate::board::piece::Piece;
+#[cfg(test)]
+use crate::board::rules::GameType;
+#[cfg(test)]
+use crate::board::turn::Move;
+
+/// plays the step from `from` to `to` on `board` and records it
+#[cfg(test)]
+fn step(tracker: &mut DrawTracker, board: &mut Board, from: (usize, usize), to: (usize, usize)) {
+    let mut turn = Move::from(from);
+    turn.step(to, None);
+    let color = board[from].color();
+    let undo = board.apply(&turn);
+    tracker.record(board, color.opposite(), &undo);
+}
+
+#[test]
+fn test_win() {
+    let board = board_with(&[((4, 5), Piece::WhitePawn)]);
+    assert_eq!(
+        board.status(Color::Black),
+        GameStatus::Won {
+            winner: Color::White
+        }
+    );
+    assert_eq!(board.status(Color::White), GameStatus::Ongoing);
+
+    let blocked = board_with(&[
+        ((1, 0), Piece::WhitePawn),
+        ((0, 1), Piece::BlackPawn),
+        ((1, 2), Piece::WhitePawn),
+        ((2, 3), Piece::WhitePawn),
+    ]);
+    assert_eq!(
+        blocked.status(Color::Black),
+        GameStatus::Won {
+            winner: Color::White
+        }
+    );
+    assert_eq!(
+        DrawTracker::new(&blocked, Color::Black).status(&blocked, Color::Black),
+        GameStatus::Won {
+            winner: Color::White
+        }
+    );
+}
+
+#[test]
+fn test_repetition() {
+    let mut board = board_with(&[
+        ((0, 9), Piece::WhiteQueen),
+        ((9, 0), Piece::BlackQueen),
+        ((5, 0), Piece::BlackPawn),
+        ((4, 9), Piece::WhitePawn),
+    ]);
+    let mut tracker = DrawTracker::new(&board, Color::White);
+    for round in 0..2 {
+        assert_eq!(tracker.draw(), None, "round {}", round);
+        step(&mut tracker, &mut board, (0, 9), (1, 8));
+        step(&mut tracker, &mut board, (9, 0), (8, 1));
+        step(&mut tracker, &mut board, (1, 8), (0, 9));
+        step(&mut tracker, &mut board, (8, 1), (9, 0));
+    }
+    assert_eq!(tracker.draw(), Some(DrawReason::Repetition));
+
+    step(&mut tracker, &mut board, (4, 9), (3, 8));
+    assert_eq!(tracker.draw(), None);
+}
+
+#[test]
+fn test_king_moves() {
+    let mut board = board_with(&[
+        ((0, 9), Piece::WhiteQueen),
+        ((2, 9), Piece::WhiteQueen),
+        ((9, 0), Piece::BlackQueen),
+        ((7, 0), Piece::BlackQueen),
+        ((5, 0), Piece::BlackPawn),
+        ((4, 9), Piece::WhitePawn),
+    ]);
+    let mut tracker = DrawTracker::new(&board, Color::White);
+    tracker.king_moves = tracker.king_moves_limit - 2;
+    step(&mut tracker, &mut board, (0, 9), (1, 8));
+    assert_eq!(tracker.draw(), None);
+    step(&mut tracker, &mut board, (9, 0), (8, 1));
+    assert_eq!(tracker.draw(), Some(DrawReason::KingMoves));
+
+    step(&mut tracker, &mut board, (4, 9), (3, 8));
+    assert_eq!(tracker.king_moves, 0);
+    assert_eq!(tracker.draw(), None);
+}
+
+#[test]
+fn test_five_moves() {
+    let mut board = board_with(&[
+        ((0, 9), Piece::WhiteQueen),
+        ((9, 8), Piece::WhitePawn),
+        ((3, 0), Piece::BlackQueen),
+    ]);
+    let mut tracker = DrawTracker::new(&board, Color::White);
+    for i in 0..5 {
+        assert_eq!(tracker.draw(), None);
+        step(&mut tracker, &mut board, (i, 9 - i), (i + 1, 8 - i));
+        assert_eq!(tracker.draw(), None);
+        step(&mut tracker, &mut board, (3 + i, i), (4 + i, i + 1));
+    }
+    assert_eq!(tracker.draw(), Some(DrawReason::FiveMoves));
+}
+
+#[test]
+fn test_sixteen_moves() {
+    let mut board = board_with(&[
+        ((0, 9), Piece::WhiteQueen),
+        ((2, 9), Piece::WhiteQueen),
+        ((9, 8), Piece::WhitePawn),
+        ((3, 0), Piece::BlackQueen),
+    ]);
+    let mut tracker = DrawTracker::new(&board, Color::White);
+    assert_eq!(tracker.endgame.map(|(_, plies)| plies), Some(0));
+    tracker.endgame = tracker
+        .endgame
+        .map(|(material, _)| (material, SIXTEEN_MOVES - 1));
+
+    step(&mut tracker, &mut board, (0, 9), (1, 8));
+    assert_eq!(tracker.draw(), Some(DrawReason::SixteenMoves));
+}
+
+#[test]
+fn test_english_draw_rules() {
+    // two kings beat a lone one in English draughts, which has no endgame rules
+    let (mut board, _) = Board::from_fen_for("W:WK29,K32:BK4", GameType::English).unwrap();
+    let mut tracker = DrawTracker::new(&board, Color::White);
+    assert_eq!(tracker.endgame, None);
+    assert_eq!(tracker.king_moves_limit, 80);
+    for _ in 0..3 {
+        step(&mut tracker, &mut board, (0, 7), (1, 6));
+        step(&mut tracker, &mut board, (7, 0), (6, 1));
+        step(&mut tracker, &mut board, (1, 6), (0, 7));
+        step(&mut tracker, &mut board, (6, 1), (7, 0));
+    }
+    assert_eq!(tracker.draw(), Some(DrawReason::Repetition));
+    assert_eq!(tracker.king_moves, 12);
+}
+
+#[test]
+fn test_endgame_rules() {
+    let lone = (0, 1);
+    assert_eq!(
+        endgame_rule(((0, 1), lone)).map(|(_, reason)| reason),
+        Some(DrawReason::FiveMoves)
+    );
+    assert_eq!(
+        endgame_rule((lone, (1, 1))).map(|(_, reason)| reason),
+        Some(DrawReason::FiveMoves)
+    );
+    assert_eq!(
+        endgame_rule(((0, 2), lone)).map(|(_, reason)| reason),
+        Some(DrawReason::FiveMoves)
+    );
+    assert_eq!(
+        endgame_rule(((2, 1), lone)).map(|(_, reason)| reason),
+        Some(DrawReason::SixteenMoves)
+    );
+    assert_eq!(
+        endgame_rule(((0, 3), lone)).map(|(_, reason)| reason),
+        Some(DrawReason::SixteenMoves)
+    );
+    assert_eq!(endgame_rule(((2, 0), lone)), None);
+    assert_eq!(endgame_rule(((0, 4), lone)), None);
+    assert_eq!(endgame_rule(((0, 2), (0, 2))), None);
+}
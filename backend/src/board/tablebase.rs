@@ -0,0 +1,1015 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::board::bitboard::{squares, BitBoard, BitMove, BLACK_LAST_ROW, WHITE_LAST_ROW};
+use crate::board::piece::Color;
+use crate::board::SQUARES;
+
+/// most pieces a tablebase may be generated for, the larger ones being out of reach
+/// of a single machine
+pub const MAX_PIECES: usize = 5;
+/// first bytes of a tablebase file
+const MAGIC: &[u8; 4] = b"DTB2";
+/// positions of a table compressed together, a probe decoding the runs of a single block
+const BLOCK: usize = 128;
+/// value of the placements which are no position while their table is solved,
+/// once compressed they take the value of the run they fall in
+const INVALID: u16 = u16::MAX;
+/// squares of the row a pawn promotes on, on which it never stands
+const ROW: usize = WHITE_LAST_ROW.count_ones() as usize;
+/// groups of pieces placed on their own squares, the kings being placed on the squares left
+const WHITE_PAWNS: usize = 0;
+const BLACK_PAWNS: usize = 1;
+
+/// Result of a position with perfect play, for the side to move.
+/// Serialized as `{"result": "win", "plies": 7}` or `{"result": "draw"}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum Outcome {
+    /// the side to move wins, leaving the opponent without a move after `plies`
+    Win {
+        plies: u16,
+    },
+    /// the side to move loses, being left without a move after `plies`
+    Loss {
+        plies: u16,
+    },
+    Draw,
+}
+
+impl Outcome {
+    /// returns the stored value: `0` for a draw, twice the plies for a win
+    /// and twice the plies plus one for a loss, keeping the quick results small
+    fn encode(self) -> u16 {
+        match self {
+            Outcome::Win { plies } => plies * 2,
+            Outcome::Loss { plies } => plies * 2 + 1,
+            Outcome::Draw => 0,
+        }
+    }
+
+    fn decode(value: u16) -> Self {
+        match value {
+            0 => Outcome::Draw,
+            _ if value % 2 == 1 => Outcome::Loss { plies: value / 2 },
+            _ => Outcome::Win { plies: value / 2 },
+        }
+    }
+}
+
+/// Pieces of both sides in an endgame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Material {
+    pub white_pawns: u8,
+    pub white_kings: u8,
+    pub black_pawns: u8,
+    pub black_kings: u8,
+}
+
+impl Material {
+    pub fn of(bits: &BitBoard) -> Self {
+        let [white_pawns, black_pawns, white_kings, black_kings] =
+            groups(bits).map(|mask| mask.count_ones() as u8);
+        Self {
+            white_pawns,
+            white_kings,
+            black_pawns,
+            black_kings,
+        }
+    }
+
+    pub fn pieces(&self) -> usize {
+        self.counts().iter().sum()
+    }
+
+    /// returns the material of the position turned around by `mirror`
+    fn mirror(&self) -> Self {
+        Self {
+            white_pawns: self.black_pawns,
+            white_kings: self.black_kings,
+            black_pawns: self.white_pawns,
+            black_kings: self.white_kings,
+        }
+    }
+
+    fn pawns(&self) -> usize {
+        (self.white_pawns + self.black_pawns) as usize
+    }
+
+    fn counts(&self) -> [usize; 4] {
+        [
+            self.white_pawns as usize,
+            self.white_kings as usize,
+            self.black_pawns as usize,
+            self.black_kings as usize,
+        ]
+    }
+
+    /// returns the number of pieces of each group, in the order `groups` gives them
+    fn placed(&self) -> [usize; 4] {
+        let [white_pawns, white_kings, black_pawns, black_kings] = self.counts();
+        [white_pawns, black_pawns, white_kings, black_kings]
+    }
+
+    /// returns the number of squares each group is placed on: the pawns on every square
+    /// but the row they promote on, whether a pawn of the other side stands there or not,
+    /// the kings on the squares left free.
+    /// Returns `None` if the pawns do not leave enough squares.
+    fn domains(&self) -> Option<[usize; 4]> {
+        let [white_pawns, black_pawns, white_kings, _] = self.placed();
+        let free = SQUARES.checked_sub(white_pawns + black_pawns)?;
+        Some([
+            SQUARES - ROW,
+            SQUARES - ROW,
+            free,
+            free.checked_sub(white_kings)?,
+        ])
+    }
+
+    /// returns every material of `pieces` pieces with at least one piece on each side
+    fn with_pieces(pieces: usize) -> Vec<Self> {
+        let mut materials = Vec::new();
+        for white in 1..pieces {
+            let black = pieces - white;
+            for white_kings in 0..=white {
+                for black_kings in 0..=black {
+                    materials.push(Self {
+                        white_pawns: (white - white_kings) as u8,
+                        white_kings: white_kings as u8,
+                        black_pawns: (black - black_kings) as u8,
+                        black_kings: black_kings as u8,
+                    });
+                }
+            }
+        }
+        materials
+    }
+
+    /// returns the number of ways to place the pieces, white pawns first, then black pawns,
+    /// white kings and black kings, each group on its `domains`.
+    /// Returns `None` if the pieces do not fit on the board or the number overflows.
+    fn size(&self) -> Option<usize> {
+        let mut size: usize = 1;
+        for (&count, &domain) in self.placed().iter().zip(self.domains()?.iter()) {
+            size = size.checked_mul(checked_binomial(domain, count)?)?;
+        }
+        Some(size)
+    }
+
+    /// returns the index of `bits`, which has this material, among the placements
+    fn index(&self, bits: &BitBoard) -> usize {
+        let counts = self.placed();
+        let domains = self.domains().expect("the material fits on the board");
+        let mut index = 0;
+        let mut taken: u64 = 0;
+        for (group, &mask) in groups(bits).iter().enumerate() {
+            let rank: usize = squares(mask)
+                .enumerate()
+                .map(|(i, square)| binomial(relative(group, taken, square), i + 1))
+                .sum();
+            index = index * binomial(domains[group], counts[group]) + rank;
+            taken |= mask;
+        }
+        index
+    }
+
+    /// returns the placement with given `index`
+    fn position(&self, mut index: usize) -> BitBoard {
+        let counts = self.placed();
+        let domains = self.domains().expect("the material fits on the board");
+        let mut ranks = [0; 4];
+        for group in (0..4).rev() {
+            let placements = binomial(domains[group], counts[group]);
+            ranks[group] = index % placements;
+            index /= placements;
+        }
+
+        let mut masks = [0; 4];
+        let mut taken = 0;
+        for group in 0..4 {
+            let mut rank = ranks[group];
+            for i in (1..=counts[group]).rev() {
+                let mut relative = i - 1;
+                while binomial(relative + 1, i) <= rank {
+                    relative += 1;
+                }
+                rank -= binomial(relative, i);
+                masks[group] |= 1 << absolute(group, taken, relative);
+            }
+            taken |= masks[group];
+        }
+
+        let [white_pawns, black_pawns, white_kings, black_kings] = masks;
+        BitBoard {
+            white: white_pawns | white_kings,
+            black: black_pawns | black_kings,
+            kings: white_kings | black_kings,
+        }
+    }
+}
+
+/// returns white pawns, black pawns, white kings and black kings, in the order they are placed
+fn groups(bits: &BitBoard) -> [u64; 4] {
+    [
+        bits.white & !bits.kings,
+        bits.black & !bits.kings,
+        bits.white & bits.kings,
+        bits.black & bits.kings,
+    ]
+}
+
+/// returns the place of `square` among the squares a piece of `group` is placed on,
+/// `taken` holding the pieces of the groups placed before
+fn relative(group: usize, taken: u64, square: usize) -> usize {
+    match group {
+        WHITE_PAWNS => square - ROW,
+        BLACK_PAWNS => square,
+        _ => square - (taken & ((1 << square) - 1)).count_ones() as usize,
+    }
+}
+
+/// returns the square of place `relative` among the squares a piece of `group` is placed on
+fn absolute(group: usize, taken: u64, relative: usize) -> usize {
+    match group {
+        WHITE_PAWNS => relative + ROW,
+        BLACK_PAWNS => relative,
+        _ => nth_free(taken, relative),
+    }
+}
+
+/// returns `n` choose `k` for the placements of a material, which are known to fit
+fn binomial(n: usize, k: usize) -> usize {
+    checked_binomial(n, k).expect("placements of a solved material fit")
+}
+
+/// returns `n` choose `k`, `None` if it overflows
+fn checked_binomial(n: usize, k: usize) -> Option<usize> {
+    if k > n {
+        return Some(0);
+    }
+    (0..k).try_fold(1usize, |result, i| {
+        Some(result.checked_mul(n - i)? / (i + 1))
+    })
+}
+
+/// returns the square of index `n` among those not in `taken`
+fn nth_free(taken: u64, n: usize) -> usize {
+    (0..SQUARES)
+        .filter(|square| taken & (1 << square) == 0)
+        .nth(n)
+        .expect("there are enough free squares")
+}
+
+/// returns `true` if the placement is a position: no square holds a white and a black pawn,
+/// and no pawn stands on the row it promotes on
+fn is_valid(bits: &BitBoard) -> bool {
+    let pawns = !bits.kings;
+    bits.white & bits.black == 0
+        && bits.white & pawns & WHITE_LAST_ROW == 0
+        && bits.black & pawns & BLACK_LAST_ROW == 0
+}
+
+/// returns the position turned half around with the colors swapped,
+/// in which white stands for black
+fn mirror(bits: &BitBoard) -> BitBoard {
+    let flip = |mask: u64| mask.reverse_bits() >> (64 - SQUARES);
+    BitBoard {
+        white: flip(bits.black),
+        black: flip(bits.white),
+        kings: flip(bits.kings),
+    }
+}
+
+/// returns `true` if `turn` of white crowns a pawn
+fn promotes(bits: &BitBoard, turn: &BitMove) -> bool {
+    bits.kings & 1 << turn.from == 0 && WHITE_LAST_ROW & 1 << turn.to != 0
+}
+
+/// returns the bucket of the positions solved after `plies`
+fn bucket<T>(buckets: &mut Vec<Vec<T>>, plies: usize) -> &mut Vec<T> {
+    if buckets.len() <= plies {
+        buckets.resize_with(plies + 1, Vec::new);
+    }
+    &mut buckets[plies]
+}
+
+/// appends `value` to `bytes` seven bits at a time, lowest first,
+/// the high bit of a byte telling that more follow
+fn push_varint(bytes: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+/// reads a value written by `push_varint` at `offset`, moving it past the value.
+/// Returns `None` if the bytes end first or the value does not fit in 16 bits.
+fn read_varint(bytes: &[u8], offset: &mut usize) -> Option<usize> {
+    let mut value = 0;
+    for shift in (0..=14).step_by(7) {
+        let byte = *bytes.get(*offset)?;
+        *offset += 1;
+        value |= ((byte & 0x7f) as usize) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value).filter(|&value| value <= u16::MAX as usize);
+        }
+    }
+    None
+}
+
+/// Values of the positions of a material, compressed.
+///
+/// The values are cut in blocks of `BLOCK` positions, each block being stored as runs of the
+/// same value, written as the value then the length of the run minus one. The placements
+/// which are no position take the value of the run they fall in, costing nothing.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Table {
+    /// number of positions
+    length: usize,
+    runs: Vec<u8>,
+    /// offset in `runs` of the first run of each block
+    blocks: Vec<u32>,
+}
+
+impl Table {
+    /// compresses `values`, in which the `INVALID` ones may take any value
+    fn compress(values: &[u16]) -> Self {
+        let mut runs = Vec::new();
+        let mut blocks = Vec::with_capacity(values.len() / BLOCK + 1);
+        for block in values.chunks(BLOCK) {
+            blocks.push(u32::try_from(runs.len()).expect("a table compresses below 4 GB"));
+            let mut run = (INVALID, 0);
+            for &value in block {
+                run = match run {
+                    (_, 0) => (value, 1),
+                    (current, length) if value == current || value == INVALID => {
+                        (current, length + 1)
+                    }
+                    (INVALID, length) => (value, length + 1),
+                    (current, length) => {
+                        push_varint(&mut runs, current as usize);
+                        push_varint(&mut runs, length - 1);
+                        (value, 1)
+                    }
+                };
+            }
+            let (current, length) = run;
+            let current = if current == INVALID { 0 } else { current };
+            push_varint(&mut runs, current as usize);
+            push_varint(&mut runs, length - 1);
+        }
+        Self {
+            length: values.len(),
+            runs,
+            blocks,
+        }
+    }
+
+    /// returns the table of `length` positions stored as `runs`,
+    /// checking that they fill every block exactly
+    fn from_runs(length: usize, runs: Vec<u8>) -> Result<Self, String> {
+        let mut blocks = Vec::with_capacity(length / BLOCK + 1);
+        let mut offset = 0;
+        for start in (0..length).step_by(BLOCK) {
+            blocks.push(u32::try_from(offset).map_err(|_| "the runs are too long")?);
+            let mut left = BLOCK.min(length - start);
+            while left > 0 {
+                let run = read_varint(&runs, &mut offset)
+                    .and(read_varint(&runs, &mut offset))
+                    .ok_or("the runs are cut")?;
+                left = left
+                    .checked_sub(run + 1)
+                    .ok_or("a run goes past its block")?;
+            }
+        }
+        if offset != runs.len() {
+            return Err("the runs go past the positions".to_string());
+        }
+        Ok(Self {
+            length,
+            runs,
+            blocks,
+        })
+    }
+
+    fn len(&self) -> usize {
+        self.length
+    }
+
+    /// returns the value of the position with given `index`
+    fn get(&self, index: usize) -> u16 {
+        let mut offset = self.blocks[index / BLOCK] as usize;
+        let mut skipped = index % BLOCK;
+        loop {
+            let value = read_varint(&self.runs, &mut offset).expect("the runs are checked");
+            let length = read_varint(&self.runs, &mut offset).expect("the runs are checked") + 1;
+            if skipped < length {
+                return value as u16;
+            }
+            skipped -= length;
+        }
+    }
+}
+
+/// Results of every position with few enough pieces.
+///
+/// Only positions with white to move are stored, positions with black to move being looked
+/// up turned around. The draw rules of the game are not taken into account: a position
+/// is drawn when neither side can force a win.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Tablebase {
+    pieces: usize,
+    tables: HashMap<Material, Table>,
+}
+
+impl Tablebase {
+    /// solves every position with up to `pieces` pieces, going backwards from the positions
+    /// in which the side to move has no move left or captures into a smaller table.
+    ///
+    /// The tables are solved a material and its mirror at a time, fewest pawns first, so that
+    /// only those tables are ever held whole, at three bytes a position; the solved tables
+    /// are kept compressed. Measured on a single core of a release build:
+    ///
+    /// | pieces | time    | peak memory | file    |
+    /// |--------|---------|-------------|---------|
+    /// | 3      | 1.3 s   | 23 MB       | 0.6 MB  |
+    /// | 4      | 104 s   | 147 MB      | 31 MB   |
+    /// | 5      | 62 min  | 2.1 GB      | 1.3 GB  |
+    ///
+    /// 6 pieces have never been generated, so neither their time nor their size is known,
+    /// and asking for more than `MAX_PIECES` panics.
+    pub fn generate(pieces: usize) -> Self {
+        assert!(
+            pieces <= MAX_PIECES,
+            "tablebases go up to {} pieces",
+            MAX_PIECES
+        );
+        let mut tablebase = Tablebase::default();
+        for count in 2..=pieces {
+            tablebase.solve(count);
+            tablebase.pieces = count;
+        }
+        tablebase
+    }
+
+    /// returns the most pieces a position may have to be found in the tablebase
+    pub fn pieces(&self) -> usize {
+        self.pieces
+    }
+
+    /// returns the outcome of the position with `color` to move,
+    /// `None` if it has too many pieces or one side has none
+    pub fn probe(&self, bits: &BitBoard, color: Color) -> Option<Outcome> {
+        if bits.occupied().count_ones() as usize > self.pieces {
+            return None;
+        }
+        let bits = match color {
+            Color::Black => mirror(bits),
+            _ => *bits,
+        };
+        self.lookup(&bits)
+    }
+
+    /// returns the outcome of `bits`, with white to move, `None` if its table is not solved
+    fn lookup(&self, bits: &BitBoard) -> Option<Outcome> {
+        let material = Material::of(bits);
+        let table = self.tables.get(&material)?;
+        Some(Outcome::decode(table.get(material.index(bits))))
+    }
+
+    /// fills the tables of `count` pieces, the smaller ones being solved already,
+    /// a material and its mirror at a time, fewest pawns first
+    fn solve(&mut self, count: usize) {
+        let mut groups: Vec<Vec<Material>> = Material::with_pieces(count)
+            .into_iter()
+            .filter(|material| *material <= material.mirror())
+            .map(|material| {
+                let mut group = vec![material, material.mirror()];
+                group.dedup();
+                group
+            })
+            .collect();
+        groups.sort_by_key(|group| group[0].pawns());
+        for group in groups {
+            self.solve_group(&group);
+        }
+    }
+
+    /// fills the tables of `group`, a material and its mirror, which lead to each other
+    /// by steps and slides. Captures lead to the smaller tables and promotions to the tables
+    /// with fewer pawns, solved already.
+    ///
+    /// A first pass solves the positions ending right away: those without a move, and those
+    /// with captures. The other positions are solved going backwards: pass `plies` takes back
+    /// the moves leading to the positions ending after `plies`, and the promotions leading to
+    /// such positions, a position being won as soon as one of its moves leads to a lost one,
+    /// and lost once all its moves lead to won ones. Positions left are drawn.
+    fn solve_group(&mut self, group: &[Material]) {
+        let mut tables: HashMap<Material, Vec<u16>> = HashMap::new();
+        // moves of the positions left to solve not known to lead to a won position yet,
+        // the promotions counting as one; `0` for the solved and the invalid positions
+        let mut remaining: HashMap<Material, Vec<u8>> = HashMap::new();
+        let mut solved: Vec<Vec<(Material, usize)>> = Vec::new();
+        // positions whose promotions lead to a position lost, or all to positions won,
+        // after `plies`
+        let mut promoted: Vec<Vec<(Material, usize, bool)>> = Vec::new();
+
+        for &material in group {
+            let size = material.size().expect("the material fits on the board");
+            let (mut values, moves): (Vec<u16>, Vec<u8>) = (0..size)
+                .into_par_iter()
+                .map(|index| {
+                    let bits = material.position(index);
+                    if !is_valid(&bits) {
+                        return (INVALID, 0);
+                    }
+                    let moves = bits.legal_moves(Color::White);
+                    match moves.first() {
+                        None => (Outcome::Loss { plies: 0 }.encode(), 0),
+                        Some(turn) if turn.captured != 0 => {
+                            (self.outcome_after(&bits, &moves).encode(), 0)
+                        }
+                        Some(_) => {
+                            let (promotions, steps): (Vec<BitMove>, Vec<BitMove>) =
+                                moves.into_iter().partition(|turn| promotes(&bits, turn));
+                            let count = steps.len() + usize::from(!promotions.is_empty());
+                            let count = u8::try_from(count).expect("few pieces move");
+                            // the outcome the promotions alone lead to, until it is reached
+                            (self.outcome_after(&bits, &promotions).encode(), count)
+                        }
+                    }
+                })
+                .unzip();
+
+            for (index, value) in values.iter_mut().enumerate() {
+                let (plies, lost) = match Outcome::decode(*value) {
+                    _ if *value == INVALID => continue,
+                    Outcome::Win { plies } => (plies as usize, true),
+                    Outcome::Loss { plies } => (plies as usize, false),
+                    Outcome::Draw => continue,
+                };
+                if moves[index] == 0 {
+                    bucket(&mut solved, plies).push((material, index));
+                } else {
+                    bucket(&mut promoted, plies - 1).push((material, index, lost));
+                    *value = 0;
+                }
+            }
+            tables.insert(material, values);
+            remaining.insert(material, moves);
+        }
+
+        let mut plies = 0;
+        while plies < solved.len().max(promoted.len()) {
+            let positions = std::mem::take(bucket(&mut solved, plies));
+            let mut predecessors = std::mem::take(bucket(&mut promoted, plies));
+            // the moves are taken back in chunks, not to hold all of them at once
+            let mut chunks = positions.chunks(1 << 16);
+            loop {
+                for (material, index, lost) in predecessors.drain(..) {
+                    let left = &mut remaining.get_mut(&material).expect("in the group")[index];
+                    let outcome = match (*left, lost) {
+                        (0, _) => continue,
+                        (_, true) => Outcome::Win {
+                            plies: plies as u16 + 1,
+                        },
+                        (1, false) => Outcome::Loss {
+                            plies: plies as u16 + 1,
+                        },
+                        (_, false) => {
+                            *left -= 1;
+                            continue;
+                        }
+                    };
+                    *left = 0;
+                    tables.get_mut(&material).expect("in the group")[index] = outcome.encode();
+                    bucket(&mut solved, plies + 1).push((material, index));
+                }
+
+                let chunk = match chunks.next() {
+                    Some(chunk) => chunk,
+                    None => break,
+                };
+                let tables = &tables;
+                predecessors.par_extend(chunk.par_iter().flat_map_iter(|&(material, index)| {
+                    let lost = matches!(
+                        Outcome::decode(tables[&material][index]),
+                        Outcome::Loss { .. }
+                    );
+                    mirror(&material.position(index))
+                        .quiet_predecessors(Color::White)
+                        .into_iter()
+                        .filter_map(move |previous| {
+                            let material = Material::of(&previous);
+                            // crowning moves taken back lead to the groups solved later
+                            if !tables.contains_key(&material) {
+                                return None;
+                            }
+                            Some((material, material.index(&previous), lost))
+                        })
+                }));
+            }
+            plies += 1;
+        }
+
+        for (material, values) in tables {
+            self.tables.insert(material, Table::compress(&values));
+        }
+    }
+
+    /// returns the outcome of `bits`, with white to move, if `moves` were its only moves,
+    /// all leading to solved tables: `Draw` if there are none
+    fn outcome_after(&self, bits: &BitBoard, moves: &[BitMove]) -> Outcome {
+        if moves.is_empty() {
+            return Outcome::Draw;
+        }
+        let mut quickest_loss = None;
+        let mut slowest_win = Some(0);
+        for turn in moves {
+            let mut next = *bits;
+            next.apply(turn);
+            let next = mirror(&next);
+            let outcome = if next.white == 0 {
+                Outcome::Loss { plies: 0 }
+            } else {
+                self.lookup(&next).expect("smaller tables are solved first")
+            };
+            match outcome {
+                Outcome::Loss { plies } => {
+                    quickest_loss =
+                        Some(quickest_loss.map_or(plies, |quickest: u16| quickest.min(plies)))
+                }
+                Outcome::Win { plies } => {
+                    slowest_win = slowest_win.map(|slowest: u16| slowest.max(plies))
+                }
+                Outcome::Draw => slowest_win = None,
+            }
+        }
+
+        match (quickest_loss, slowest_win) {
+            (Some(quickest), _) => Outcome::Win {
+                plies: quickest + 1,
+            },
+            (None, Some(slowest)) => Outcome::Loss { plies: slowest + 1 },
+            (None, None) => Outcome::Draw,
+        }
+    }
+
+    /// writes the tablebase: the magic bytes, the number of pieces, the number of tables,
+    /// then every table as its material, its number of positions, the length of its runs
+    /// and its runs, little-endian
+    pub fn write<W>(&self, writer: W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        let mut writer = BufWriter::new(writer);
+        let mut materials: Vec<&Material> = self.tables.keys().collect();
+        materials.sort();
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[self.pieces as u8])?;
+        writer.write_all(&(materials.len() as u32).to_le_bytes())?;
+        for material in materials {
+            let table = &self.tables[material];
+            let counts = material.counts().map(|count| count as u8);
+            writer.write_all(&counts)?;
+            writer.write_all(&(table.len() as u64).to_le_bytes())?;
+            writer.write_all(&(table.runs.len() as u64).to_le_bytes())?;
+            writer.write_all(&table.runs)?;
+        }
+        writer.flush()
+    }
+
+    /// reads a tablebase written by `write`
+    pub fn read<R>(reader: R) -> Result<Self, TablebaseError>
+    where
+        R: Read,
+    {
+        let mut reader = BufReader::new(reader);
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(TablebaseError::Format("not a tablebase".to_string()));
+        }
+        let mut pieces = [0; 1];
+        reader.read_exact(&mut pieces)?;
+        let mut count = [0; 4];
+        reader.read_exact(&mut count)?;
+
+        let mut tables = HashMap::new();
+        for _ in 0..u32::from_le_bytes(count) {
+            let mut counts = [0; 4];
+            reader.read_exact(&mut counts)?;
+            let [white_pawns, white_kings, black_pawns, black_kings] = counts;
+            let material = Material {
+                white_pawns,
+                white_kings,
+                black_pawns,
+                black_kings,
+            };
+            let has_both_sides = white_pawns | white_kings != 0 && black_pawns | black_kings != 0;
+            if !has_both_sides || material.pieces() > pieces[0] as usize {
+                return Err(TablebaseError::Format(format!(
+                    "table of {:?} in a tablebase of {} pieces",
+                    material, pieces[0]
+                )));
+            }
+            let mut length = [0; 8];
+            reader.read_exact(&mut length)?;
+            let length = u64::from_le_bytes(length);
+            let size = match material.size() {
+                Some(size) if size as u64 == length => size,
+                _ => {
+                    return Err(TablebaseError::Format(format!(
+                        "table of {:?} has {} positions",
+                        material, length
+                    )))
+                }
+            };
+
+            // the runs are read as they come, a cut file is not trusted with their length
+            let mut runs_length = [0; 8];
+            reader.read_exact(&mut runs_length)?;
+            let runs_length = u64::from_le_bytes(runs_length);
+            let mut runs = Vec::new();
+            (&mut reader).take(runs_length).read_to_end(&mut runs)?;
+            if runs.len() as u64 != runs_length {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+            }
+            let table = Table::from_runs(size, runs).map_err(|error| {
+                TablebaseError::Format(format!("table of {:?}: {}", material, error))
+            })?;
+            tables.insert(material, table);
+        }
+
+        Ok(Self {
+            pieces: pieces[0] as usize,
+            tables,
+        })
+    }
+
+    pub fn save<P>(&self, path: P) -> io::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        self.write(File::create(path)?)
+    }
+
+    pub fn load<P>(path: P) -> Result<Self, TablebaseError>
+    where
+        P: AsRef<Path>,
+    {
+        Tablebase::read(File::open(path)?)
+    }
+}
+
+/// Reasons a tablebase can not be read
+#[derive(Debug)]
+pub enum TablebaseError {
+    Io(io::Error),
+    /// the file is not a tablebase or its tables do not fit their material
+    Format(String),
+}
+
+impl From<io::Error> for TablebaseError {
+    fn from(error: io::Error) -> Self {
+        TablebaseError::Io(error)
+    }
+}
+
+impl fmt::Display for TablebaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TablebaseError::Io(error) => write!(f, "can not read the tablebase: {}", error),
+            TablebaseError::Format(error) => write!(f, "invalid tablebase: {}", error),
+        }
+    }
+}
+
+impl Error for TablebaseError {}
+
+#[cfg(test)]
+use crate::board::search::{search, SearchSettings, Threads, WIN_SCORE};
+#[cfg(test)]
+use crate::board::{evaluation::Weights, Board};
+#[cfg(test)]
+use std::sync::Arc;
+
+#[cfg(test)]
+fn probe_fen(tablebase: &Tablebase, fen: &str) -> Option<Outcome> {
+    let (board, color) = Board::from_fen(fen).unwrap();
+    tablebase.probe(&BitBoard::from(&board), color)
+}
+
+#[test]
+fn test_indexes() {
+    for material in Material::with_pieces(3) {
+        let size = material.size().unwrap();
+        for index in (0..size).step_by(size / 97 + 1).chain(Some(size - 1)) {
+            let bits = material.position(index);
+            if !is_valid(&bits) {
+                continue;
+            }
+            assert_eq!(Material::of(&bits), material);
+            assert_eq!(material.index(&bits), index, "{:?}", material);
+        }
+    }
+
+    // the pawns are never placed on the row they promote on,
+    // only a white and a black pawn on the same square are left to skip
+    let material = Material {
+        white_pawns: 1,
+        white_kings: 0,
+        black_pawns: 1,
+        black_kings: 1,
+    };
+    assert_eq!(material.size(), Some(45 * 45 * 48));
+    let valid = (0..45 * 45)
+        .filter(|&index| is_valid(&material.position(index * 48)))
+        .count();
+    assert_eq!(valid, 45 * 45 - 40);
+}
+
+#[test]
+fn test_table() {
+    let mut values = vec![0; 1000];
+    values[3] = Outcome::Win { plies: 200 }.encode();
+    values[128..300].iter_mut().for_each(|value| *value = 7);
+    values[400..410]
+        .iter_mut()
+        .for_each(|value| *value = INVALID);
+    values[999] = INVALID;
+    let table = Table::compress(&values);
+    assert_eq!(table.len(), 1000);
+    assert_eq!(table.blocks.len(), 8);
+    assert!(table.runs.len() < 40);
+    for (index, &value) in values.iter().enumerate() {
+        if value != INVALID {
+            assert_eq!(table.get(index), value);
+        }
+    }
+    assert_eq!(
+        Table::from_runs(1000, table.runs.clone()),
+        Ok(table.clone())
+    );
+
+    let mut longer = table.runs.clone();
+    longer.extend_from_slice(&[0, 0]);
+    assert!(Table::from_runs(1000, longer).is_err());
+    assert!(Table::from_runs(1000, table.runs[..table.runs.len() - 1].to_vec()).is_err());
+    assert!(Table::from_runs(1001, table.runs.clone()).is_err());
+    // a run of 200 positions in a block of 128
+    assert!(Table::from_runs(128, vec![0, 0xc7, 0x01]).is_err());
+    assert!(Table::from_runs(1, vec![0xff, 0xff, 0xff, 0]).is_err());
+}
+
+#[test]
+fn test_mirror() {
+    let (board, _) = Board::from_fen("W:W28,K46:B5,K23").unwrap();
+    let bits = BitBoard::from(&board);
+    let (mirrored, _) = Board::from_fen("W:W46,K28:B23,K5").unwrap();
+    assert_eq!(mirror(&bits), BitBoard::from(&mirrored));
+    assert_eq!(mirror(&mirror(&bits)), bits);
+}
+
+#[test]
+fn test_two_pieces() {
+    let tablebase = Tablebase::generate(2);
+    assert_eq!(tablebase.pieces(), 2);
+    assert_eq!(
+        probe_fen(&tablebase, "W:W28:B23"),
+        Some(Outcome::Win { plies: 1 })
+    );
+    assert_eq!(
+        probe_fen(&tablebase, "B:W28:B23"),
+        Some(Outcome::Win { plies: 1 })
+    );
+    assert_eq!(probe_fen(&tablebase, "W:WK50:BK1"), Some(Outcome::Draw));
+    assert_eq!(probe_fen(&tablebase, "W:W28,33:B23"), None);
+
+    // every result the search reaches agrees with the tablebase
+    let settings = SearchSettings {
+        threads: Threads::Single,
+        table_size: 0,
+        ..SearchSettings::default()
+    };
+    let weights = Weights::material();
+    let mut checked = 0;
+    for (material, table) in &tablebase.tables {
+        for index in (0..table.len()).step_by(37) {
+            let bits = material.position(index);
+            let expected = match Outcome::decode(table.get(index)) {
+                Outcome::Win { plies } if plies <= 5 => WIN_SCORE - plies as i32,
+                Outcome::Loss { plies } if (1..=5).contains(&plies) => -WIN_SCORE + plies as i32,
+                _ => continue,
+            };
+            if !is_valid(&bits) {
+                continue;
+            }
+            let settings = SearchSettings {
+                depth: 6,
+                ..settings.clone()
+            };
+            let (_, score) = search(&bits, Color::White, &settings, &weights).unwrap();
+            assert_eq!(
+                score,
+                expected,
+                "{}",
+                Board::from(&bits).to_fen(Color::White)
+            );
+            checked += 1;
+        }
+    }
+    assert!(checked > 0);
+}
+
+#[test]
+fn test_search_probes() {
+    let tablebase = Tablebase::generate(2);
+    let (material, index, plies) = tablebase
+        .tables
+        .iter()
+        .flat_map(|(material, table)| {
+            (0..table.len()).map(move |index| (*material, index, Outcome::decode(table.get(index))))
+        })
+        .filter(|(material, index, _)| is_valid(&material.position(*index)))
+        .find_map(|(material, index, outcome)| match outcome {
+            Outcome::Win { plies } if plies >= 9 => Some((material, index, plies)),
+            _ => None,
+        })
+        .expect("some wins take long");
+    let bits = material.position(index);
+
+    let settings = SearchSettings {
+        depth: 2,
+        threads: Threads::Single,
+        tablebase: Some(Arc::new(tablebase)),
+        ..SearchSettings::default()
+    };
+    let (_, score) = search(&bits, Color::White, &settings, &Weights::default()).unwrap();
+    assert_eq!(score, WIN_SCORE - plies as i32);
+}
+
+#[test]
+fn test_file() {
+    let tablebase = Tablebase::generate(2);
+    let mut bytes = Vec::new();
+    tablebase.write(&mut bytes).unwrap();
+    assert_eq!(&bytes[..4], MAGIC);
+    assert_eq!(Tablebase::read(bytes.as_slice()).unwrap(), tablebase);
+
+    assert!(matches!(
+        Tablebase::read(&b"DTB1"[..]),
+        Err(TablebaseError::Format(_))
+    ));
+    assert!(matches!(
+        Tablebase::read(&bytes[..bytes.len() - 1]),
+        Err(TablebaseError::Io(_))
+    ));
+
+    // a table of more pieces than the tablebase has, of one side only,
+    // of more pieces than the board holds, or whose runs do not fill it
+    let file = |pieces: u8, counts: [u8; 4], length: u64, runs: &[u8]| {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(pieces);
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.extend_from_slice(&counts);
+        bytes.extend_from_slice(&length.to_le_bytes());
+        bytes.extend_from_slice(&(runs.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(runs);
+        bytes
+    };
+    let huge = Material {
+        white_pawns: 12,
+        white_kings: 0,
+        black_pawns: 0,
+        black_kings: 1,
+    };
+    let huge_size = huge.size().unwrap() as u64;
+    for bytes in &[
+        file(2, [12, 0, 0, 1], huge_size, &[]),
+        file(2, [2, 0, 0, 0], 1225, &[]),
+        file(255, [100, 0, 100, 0], 0, &[]),
+        file(255, [20, 20, 0, 1], 0, &[]),
+        file(2, [1, 0, 0, 1], 45 * 49, &[0x80]),
+    ] {
+        assert!(matches!(
+            Tablebase::read(bytes.as_slice()),
+            Err(TablebaseError::Format(_))
+        ));
+    }
+    let cut = file(13, [12, 0, 0, 1], huge_size, &[0, 0]);
+    assert!(matches!(
+        Tablebase::read(&cut[..cut.len() - 1]),
+        Err(TablebaseError::Io(_))
+    ));
+}
@@ -0,0 +1,192 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Kind of score stored for a position, following from the alpha-beta window it was searched with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bound {
+    /// the score is exact
+    Exact,
+    /// the search failed high, the real score is at least the stored one
+    Lower,
+    /// the search failed low, the real score is at most the stored one
+    Upper,
+}
+
+/// What the search learned about a position
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TableEntry {
+    /// plies searched below the position
+    pub depth: usize,
+    pub bound: Bound,
+    pub score: i32,
+    /// index of the best move found among the legal moves of the position
+    pub best_move: Option<usize>,
+}
+
+/// Fixed-size table of search results keyed by position hashes, shared between threads
+/// without locks.
+///
+/// Each slot keeps the key xored with the data next to the data itself.
+/// A slot torn by two threads writing at once no longer matches its key, and is ignored.
+#[derive(Debug)]
+pub struct TranspositionTable {
+    slots: Vec<Slot>,
+}
+
+#[derive(Debug, Default)]
+struct Slot {
+    check: AtomicU64,
+    data: AtomicU64,
+}
+
+impl TranspositionTable {
+    /// creates a table of `size` entries rounded down to a power of two,
+    /// a table of size `0` keeps nothing
+    pub fn new(size: usize) -> Self {
+        let size = match size {
+            0 => 0,
+            size => 1 << (usize::BITS - 1 - size.leading_zeros()),
+        };
+        Self {
+            slots: (0..size).map(|_| Slot::default()).collect(),
+        }
+    }
+
+    /// returns the number of entries the table can hold
+    pub fn size(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// returns the entry stored for the position with given `hash`
+    pub fn probe(&self, hash: u64) -> Option<TableEntry> {
+        let slot = self.slot(hash)?;
+        let data = slot.data.load(Ordering::Relaxed);
+        if data == 0 || slot.check.load(Ordering::Relaxed) ^ data != hash {
+            return None;
+        }
+        Some(unpack(data))
+    }
+
+    /// stores `entry` for the position with given `hash`, unless the slot holds
+    /// a deeper search of the same position
+    pub fn store(&self, hash: u64, entry: TableEntry) {
+        let slot = match self.slot(hash) {
+            Some(slot) => slot,
+            None => return,
+        };
+        if let Some(stored) = self.probe(hash) {
+            if stored.depth > entry.depth {
+                return;
+            }
+        }
+        let data = pack(entry);
+        slot.check.store(hash ^ data, Ordering::Relaxed);
+        slot.data.store(data, Ordering::Relaxed);
+    }
+
+    /// forgets every entry
+    pub fn clear(&self) {
+        for slot in &self.slots {
+            slot.check.store(0, Ordering::Relaxed);
+            slot.data.store(0, Ordering::Relaxed);
+        }
+    }
+
+    fn slot(&self, hash: u64) -> Option<&Slot> {
+        if self.slots.is_empty() {
+            return None;
+        }
+        Some(&self.slots[hash as usize & (self.slots.len() - 1)])
+    }
+}
+
+/// packs the entry into 64 bits: the score in the lowest 32, then 8 bits of depth,
+/// 2 bits of bound and 16 bits of best move index shifted by one, `0` meaning no move.
+/// The bound is stored shifted by one as well, so no packed entry is `0`.
+fn pack(entry: TableEntry) -> u64 {
+    let bound = match entry.bound {
+        Bound::Exact => 1,
+        Bound::Lower => 2,
+        Bound::Upper => 3,
+    };
+    let best_move = entry.best_move.map_or(0, |index| index as u64 + 1) & 0xffff;
+    u64::from(entry.score as u32)
+        | (entry.depth.min(0xff) as u64) << 32
+        | bound << 40
+        | best_move << 42
+}
+
+fn unpack(data: u64) -> TableEntry {
+    let bound = match (data >> 40) & 0b11 {
+        1 => Bound::Exact,
+        2 => Bound::Lower,
+        _ => Bound::Upper,
+    };
+    let best_move = (data >> 42) & 0xffff;
+    TableEntry {
+        depth: ((data >> 32) & 0xff) as usize,
+        bound,
+        score: data as u32 as i32,
+        best_move: best_move.checked_sub(1).map(|index| index as usize),
+    }
+}
+
+#[test]
+fn test_store_and_probe() {
+    let table = TranspositionTable::new(1000);
+    assert_eq!(table.size(), 512);
+    let entry = TableEntry {
+        depth: 4,
+        bound: Bound::Lower,
+        score: -1_000_000 + 3,
+        best_move: Some(7),
+    };
+    table.store(42, entry);
+    assert_eq!(table.probe(42), Some(entry));
+    assert_eq!(table.probe(42 + 512), None);
+
+    let shallow = TableEntry {
+        depth: 2,
+        bound: Bound::Exact,
+        score: 10,
+        best_move: None,
+    };
+    table.store(42, shallow);
+    assert_eq!(table.probe(42), Some(entry));
+    table.store(42 + 512, shallow);
+    assert_eq!(table.probe(42 + 512), Some(shallow));
+    assert_eq!(table.probe(42), None);
+
+    table.clear();
+    assert_eq!(table.probe(42 + 512), None);
+}
+
+#[test]
+fn test_torn_slot_is_ignored() {
+    let table = TranspositionTable::new(16);
+    let entry = TableEntry {
+        depth: 3,
+        bound: Bound::Exact,
+        score: 5,
+        best_move: Some(0),
+    };
+    table.store(3, entry);
+    let slot = table.slot(3).unwrap();
+    slot.data
+        .store(pack(TableEntry { score: 6, ..entry }), Ordering::Relaxed);
+    assert_eq!(table.probe(3), None);
+}
+
+#[test]
+fn test_empty_table() {
+    let table = TranspositionTable::new(0);
+    table.store(
+        1,
+        TableEntry {
+            depth: 1,
+            bound: Bound::Exact,
+            score: 0,
+            best_move: None,
+        },
+    );
+    assert_eq!(table.probe(1), None);
+}
@@ -1,22 +1,45 @@
 use std::cmp::Ordering;
 
+use serde::{Deserialize, Serialize};
+
+use crate::board::piece::Piece;
 use crate::board::position::Position;
 
-#[derive(Debug, Clone)]
+/// Move of a single piece, serialized as `{"from": [x, y], "to": [x, y], "steps": 2,
+/// "kills": [[x, y], [x, y]], "path": [[x, y], [x, y]]}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Move {
+    #[serde(rename = "from")]
     pub starting_position: Position,
+    #[serde(rename = "to")]
     pub end_position: Position,
+    #[serde(rename = "steps")]
     pub moves_counter: usize,
+    #[serde(default)]
     pub kills: Vec<Position>,
+    /// cells the piece stopped on, one for every step, the last one being `end_position`
+    #[serde(default)]
+    pub path: Vec<Position>,
+}
+
+/// Everything needed to take back a `Move` applied on a `Board`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Undo {
+    pub starting_position: Position,
+    pub end_position: Position,
+    /// the piece as it stood on the starting position, before any promotion
+    pub piece: Piece,
+    pub captured: Vec<(Position, Piece)>,
 }
 
 impl Move {
-    fn step<P, O>(&mut self, position: P, to_kill: O)
+    pub fn step<P, O>(&mut self, position: P, to_kill: O)
     where
         P: Into<Position>,
         O: Into<Option<P>>,
     {
         self.end_position = position.into();
+        self.path.push(self.end_position);
         self.moves_counter += 1;
         if let Some(killed) = to_kill.into() {
             self.kills.push(killed.into());
@@ -28,12 +51,50 @@ impl Move {
         self.end_position = other.end_position;
         self.moves_counter += other.moves_counter;
         self.kills.append(&mut other.kills);
+        self.path.append(&mut other.path);
+    }
+
+    /// returns `true` if both moves go between the same squares taking the same pieces,
+    /// no matter in which order the pieces were taken
+    pub fn is_equivalent(&self, other: &Self) -> bool {
+        if self.starting_position != other.starting_position
+            || self.end_position != other.end_position
+            || self.kills.len() != other.kills.len()
+        {
+            return false;
+        }
+        let mut kills = self.kills.clone();
+        let mut other_kills = other.kills.clone();
+        kills.sort();
+        other_kills.sort();
+        kills == other_kills
+    }
+
+    /// returns the move in the draughts notation with the square numbers of a board
+    /// of `size` cells a side: `32-28` for a step and `28x19x10` for a capture,
+    /// listing every square the piece stopped on
+    pub fn notation(&self, size: usize) -> String {
+        let square = |position: &Position| match position.square_on(size) {
+            Ok(square) => square.to_string(),
+            Err(_) => format!("{:?}", position.inner()),
+        };
+        let mut text = square(&self.starting_position);
+        if self.kills.is_empty() {
+            text += &format!("-{}", square(&self.end_position));
+        } else if self.path.is_empty() {
+            text += &format!("x{}", square(&self.end_position));
+        } else {
+            for position in &self.path {
+                text += &format!("x{}", square(position));
+            }
+        }
+        text
     }
 }
 
 impl PartialOrd for Move {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(&other))
+        Some(self.cmp(other))
     }
 }
 
@@ -62,6 +123,7 @@ where
             end_position: pos,
             moves_counter: 0,
             kills: Vec::new(),
+            path: Vec::new(),
         }
     }
 }
@@ -100,6 +162,7 @@ fn test_step_position() {
     assert_eq!(turn.kills, vec![Position((4, 5))]);
     assert_eq!(turn.end_position, Position((4, 3)));
     assert_eq!(turn.starting_position, Position((3, 6)));
+    assert_eq!(turn.path, vec![Position((5, 4)), Position((4, 3))]);
 }
 
 #[test]
@@ -155,3 +218,61 @@ fn test_step_usize_tuple_into() {
     assert_eq!(turn.end_position, Position((4, 3)));
     assert_eq!(turn.starting_position, Position((3, 6)));
 }
+
+#[test]
+fn test_is_equivalent() {
+    let mut first = Move::from((1, 1));
+    first.step((3, 3), (2, 2));
+    first.step((5, 1), (4, 2));
+
+    let mut second = Move::from((1, 1));
+    second.step((3, 3), (2, 2));
+    second.step((5, 1), (4, 2));
+    assert!(first.is_equivalent(&second));
+
+    let mut third = Move::from((1, 1));
+    third.step((3, 3), (2, 2));
+    third.step((5, 5), (4, 4));
+    assert!(!first.is_equivalent(&third));
+}
+
+#[test]
+fn test_serde_round_trip() {
+    let mut turn = Move::from((3, 6));
+    turn.step((5, 4), (4, 5));
+    let json = serde_json::to_string(&turn).unwrap();
+    assert_eq!(
+        json,
+        r#"{"from":[3,6],"to":[5,4],"steps":1,"kills":[[4,5]],"path":[[5,4]]}"#
+    );
+
+    let parsed: Move = serde_json::from_str(&json).unwrap();
+    assert!(parsed.is_equivalent(&turn));
+    assert_eq!(parsed.moves_counter, 1);
+}
+
+#[test]
+fn test_notation() {
+    let mut step = Move::from(Position::from_square(32).unwrap());
+    step.step(Position::from_square(28).unwrap(), None);
+    assert_eq!(step.notation(10), "32-28");
+
+    let mut capture = Move::from(Position::from_square(28).unwrap());
+    capture.step((6, 3), (5, 4));
+    capture.step((8, 1), (7, 2));
+    assert_eq!(capture.notation(10), "28x19x10");
+
+    capture.path.clear();
+    assert_eq!(capture.notation(10), "28x10");
+}
+
+#[test]
+fn test_notation_on_other_boards() {
+    let mut step = Move::from(Position::from_square_on(11, 8).unwrap());
+    step.step(Position::from_square_on(15, 8).unwrap(), None);
+    assert_eq!(step.notation(8), "11-15");
+
+    let mut capture = Move::from((2, 5));
+    capture.step((4, 3), (3, 4));
+    assert_eq!(capture.notation(8), "22x15");
+}
@@ -0,0 +1,99 @@
+//! Keys of the Zobrist hashing: every piece on every cell gets a random key,
+//! and the hash of a position is the xor of the keys of its pieces,
+//! xored with one more key when black is to move and one for the game type,
+//! so positions looking the same in two game types played on the same board differ.
+//!
+//! The keys come from splitmix64 with a fixed seed, so hashes stay the same between runs.
+
+use crate::board::piece::{Color, Piece};
+use crate::board::rules::GameType;
+
+/// cells of the largest board, the 12x12 Canadian one.
+/// Keys are generated cell after cell, so smaller boards use the first ones.
+const CELLS: usize = 12 * 12;
+const SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+/// keys of the white pawn, black pawn, white queen and black queen on each cell
+const PIECE_KEYS: [[u64; 4]; CELLS] = piece_keys();
+const BLACK_TO_MOVE: u64 = splitmix(SEED ^ 0xffff_ffff).1;
+/// keys of the game types in their declaration order
+const GAME_TYPE_KEYS: [u64; 7] = game_type_keys();
+
+/// advances the splitmix64 `state`, returns the new state and the generated number
+const fn splitmix(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    (state, z ^ (z >> 31))
+}
+
+const fn piece_keys() -> [[u64; 4]; CELLS] {
+    let mut keys = [[0; 4]; CELLS];
+    let mut state = SEED;
+    let mut cell = 0;
+    while cell < CELLS {
+        let mut piece = 0;
+        while piece < 4 {
+            let (next, key) = splitmix(state);
+            state = next;
+            keys[cell][piece] = key;
+            piece += 1;
+        }
+        cell += 1;
+    }
+    keys
+}
+
+const fn game_type_keys() -> [u64; 7] {
+    let mut keys = [0; 7];
+    let mut state = SEED ^ 0xffff_ffff_0000_0000;
+    // international draughts keeps no key, so its hashes and the books written with them
+    // stay what they were before the other game types came
+    let mut game_type = 1;
+    while game_type < keys.len() {
+        let (next, key) = splitmix(state);
+        state = next;
+        keys[game_type] = key;
+        game_type += 1;
+    }
+    keys
+}
+
+/// returns the key of `piece` standing on the cell with given matrix `index`,
+/// `0` for an empty cell
+pub fn piece(index: usize, piece: Piece) -> u64 {
+    let slot = match piece {
+        Piece::WhitePawn => 0,
+        Piece::BlackPawn => 1,
+        Piece::WhiteQueen => 2,
+        Piece::BlackQueen => 3,
+        Piece::Empty => return 0,
+    };
+    PIECE_KEYS[index][slot]
+}
+
+/// returns the key of `turn` being the side to move
+pub fn side(turn: Color) -> u64 {
+    match turn {
+        Color::Black => BLACK_TO_MOVE,
+        _ => 0,
+    }
+}
+
+/// returns the key of positions of `game_type`
+pub fn game_type(game_type: GameType) -> u64 {
+    GAME_TYPE_KEYS[game_type as usize]
+}
+
+#[test]
+fn test_keys_differ() {
+    let mut keys: Vec<u64> = PIECE_KEYS.iter().flatten().copied().collect();
+    keys.push(BLACK_TO_MOVE);
+    keys.extend_from_slice(&GAME_TYPE_KEYS[1..]);
+    let count = keys.len();
+    keys.sort_unstable();
+    keys.dedup();
+    assert_eq!(keys.len(), count);
+    assert!(keys.iter().all(|&key| key != 0));
+}
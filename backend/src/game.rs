@@ -0,0 +1,282 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::board::piece::Color;
+use crate::board::rules::GameType;
+use crate::board::status::{DrawTracker, GameStatus};
+use crate::board::turn::{Move, Undo};
+use crate::board::Board;
+
+/// Reasons a move can not be played or a ply can not be reached
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameError {
+    /// the move is not one of the legal moves of the side to move
+    IllegalMove,
+    /// the game was already won or drawn
+    GameOver,
+    /// the ply lies beyond the last played move
+    InvalidPly(usize),
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::IllegalMove => write!(f, "illegal move"),
+            GameError::GameOver => write!(f, "the game is over"),
+            GameError::InvalidPly(ply) => write!(f, "no move was played at ply {}", ply),
+        }
+    }
+}
+
+impl Error for GameError {}
+
+/// Game played on a `Board`: the side to move, every played move
+/// and the history needed by the draw rules.
+///
+/// The game can be replayed by going back to any earlier ply with `goto`.
+/// Moves after the current ply are kept until a new move is played there.
+#[derive(Debug, Clone)]
+pub struct Game {
+    start: Board,
+    start_turn: Color,
+    board: Board,
+    turn: Color,
+    moves: Vec<Move>,
+    /// undos of the moves up to the current ply
+    undos: Vec<Undo>,
+    /// position hashes, the one of the starting position first and then one after each move
+    hashes: Vec<u64>,
+    /// state of the draw rules at each ply
+    draws: Vec<DrawTracker>,
+}
+
+impl Game {
+    /// starts a game of international draughts from the initial position, white to move
+    pub fn new() -> Self {
+        Game::with_game_type(GameType::International)
+    }
+
+    /// starts a game of `game_type` from its initial position,
+    /// with the side its rules let move first to move
+    pub fn with_game_type(game_type: GameType) -> Self {
+        Game::from_position(Board::initial(game_type), game_type.rules().first)
+    }
+
+    /// starts a game from `board` with `turn` to move
+    pub fn from_position(board: Board, turn: Color) -> Self {
+        Self {
+            hashes: vec![board.position_hash(turn)],
+            draws: vec![DrawTracker::new(&board, turn)],
+            start: board.clone(),
+            start_turn: turn,
+            board,
+            turn,
+            moves: Vec::new(),
+            undos: Vec::new(),
+        }
+    }
+
+    /// returns the starting position and the side to move in it
+    pub fn start(&self) -> (&Board, Color) {
+        (&self.start, self.start_turn)
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// returns the rules the game is played by
+    pub fn game_type(&self) -> GameType {
+        self.board.game_type()
+    }
+
+    /// returns the side to move
+    pub fn turn(&self) -> Color {
+        self.turn
+    }
+
+    /// returns the number of moves played to reach the current position
+    pub fn ply(&self) -> usize {
+        self.undos.len()
+    }
+
+    /// returns every played move, including those after the current ply
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// returns the hash of the current position
+    pub fn hash(&self) -> u64 {
+        self.hashes[self.ply()]
+    }
+
+    /// returns how many times the current position occurred up to the current ply,
+    /// counting itself
+    pub fn repetitions(&self) -> usize {
+        let hash = self.hash();
+        self.hashes[..=self.ply()]
+            .iter()
+            .filter(|&&other| other == hash)
+            .count()
+    }
+
+    /// returns moves the side to move can play
+    pub fn legal_moves(&self) -> Vec<Move> {
+        self.board.legal_moves(self.turn)
+    }
+
+    /// returns whether the game goes on, was won or drawn in the current position
+    pub fn status(&self) -> GameStatus {
+        self.draws[self.ply()].status(&self.board, self.turn)
+    }
+
+    /// plays `turn`, which has to be equivalent to one of the legal moves, and passes the turn.
+    /// Moves played after the current ply are dropped.
+    pub fn play(&mut self, turn: &Move) -> Result<(), GameError> {
+        if self.status().is_over() {
+            return Err(GameError::GameOver);
+        }
+        let turn = self
+            .legal_moves()
+            .into_iter()
+            .find(|legal| legal.is_equivalent(turn))
+            .ok_or(GameError::IllegalMove)?;
+
+        let ply = self.ply();
+        self.moves.truncate(ply);
+        self.hashes.truncate(ply + 1);
+        self.draws.truncate(ply + 1);
+
+        let undo = self.board.apply(&turn);
+        self.turn = self.turn.opposite();
+        let mut draws = self.draws[ply].clone();
+        draws.record(&self.board, self.turn, &undo);
+
+        self.hashes.push(self.board.position_hash(self.turn));
+        self.draws.push(draws);
+        self.undos.push(undo);
+        self.moves.push(turn);
+        Ok(())
+    }
+
+    /// takes back or replays moves until the position after `ply` moves is reached
+    pub fn goto(&mut self, ply: usize) -> Result<(), GameError> {
+        if ply > self.moves.len() {
+            return Err(GameError::InvalidPly(ply));
+        }
+        while self.ply() > ply {
+            let undo = self.undos.pop().expect("every ply has its undo");
+            self.board.undo(undo);
+            self.turn = self.turn.opposite();
+        }
+        while self.ply() < ply {
+            let undo = self.board.apply(&self.moves[self.ply()]);
+            self.undos.push(undo);
+            self.turn = self.turn.opposite();
+        }
+        Ok(())
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+use crate::board::piece::Piece;
+#[cfg(test)]
+use crate::board::position::Position;
+
+/// returns the legal move of `game` going between given squares
+#[cfg(test)]
+fn find(game: &Game, from: usize, to: usize) -> Move {
+    let from = Position::from_square(from).unwrap();
+    let to = Position::from_square(to).unwrap();
+    game.legal_moves()
+        .into_iter()
+        .find(|turn| turn.starting_position == from && turn.end_position == to)
+        .unwrap()
+}
+
+#[test]
+fn test_play() {
+    let mut game = Game::new();
+    let turn = find(&game, 32, 28);
+    game.play(&turn).unwrap();
+    assert_eq!(game.turn(), Color::Black);
+    assert_eq!(game.ply(), 1);
+    assert_eq!(game.moves().len(), 1);
+    assert_eq!(
+        game.board()[Position::from_square(32).unwrap()],
+        Piece::Empty
+    );
+
+    assert_eq!(game.play(&turn), Err(GameError::IllegalMove));
+    assert_eq!(game.ply(), 1);
+}
+
+#[test]
+fn test_goto() {
+    let mut game = Game::new();
+    for (from, to) in &[(32, 28), (19, 23), (28, 19), (14, 23)] {
+        let turn = find(&game, *from, *to);
+        game.play(&turn).unwrap();
+    }
+    let end = game.board().clone();
+
+    game.goto(0).unwrap();
+    assert_eq!(game.board(), &Board::new());
+    assert_eq!(game.turn(), Color::White);
+    assert_eq!(game.moves().len(), 4);
+
+    game.goto(3).unwrap();
+    assert_eq!(game.turn(), Color::Black);
+    game.goto(4).unwrap();
+    assert_eq!(game.board(), &end);
+    assert_eq!(game.goto(5), Err(GameError::InvalidPly(5)));
+
+    game.goto(1).unwrap();
+    let turn = find(&game, 18, 22);
+    game.play(&turn).unwrap();
+    assert_eq!(game.ply(), 2);
+    assert_eq!(game.moves().len(), 2);
+    assert_eq!(game.turn(), Color::White);
+}
+
+#[test]
+fn test_repetitions() {
+    let (board, turn) = Board::from_fen("W:WK46,36:BK1,11").unwrap();
+    let mut game = Game::from_position(board, turn);
+    for round in 1..=2 {
+        for (from, to) in &[(46, 41), (1, 6), (41, 46), (6, 1)] {
+            let turn = find(&game, *from, *to);
+            game.play(&turn).unwrap();
+        }
+        assert_eq!(game.repetitions(), round + 1);
+    }
+    assert!(game.status().is_over());
+    assert_eq!(game.play(&find(&game, 36, 31)), Err(GameError::GameOver));
+
+    game.goto(4).unwrap();
+    assert_eq!(game.repetitions(), 2);
+    assert!(!game.status().is_over());
+}
+
+#[test]
+fn test_english_game() {
+    let mut game = Game::with_game_type(GameType::English);
+    assert_eq!(game.game_type(), GameType::English);
+    assert_eq!(game.turn(), Color::Black);
+    assert_eq!(game.legal_moves().len(), 7);
+
+    let turn = game
+        .legal_moves()
+        .into_iter()
+        .find(|turn| turn.notation(8) == "11-15")
+        .unwrap();
+    game.play(&turn).unwrap();
+    assert_eq!(game.turn(), Color::White);
+    assert_eq!(game.board().to_fen(game.turn()), "W:W21-32:B1-10,12,15");
+}
@@ -0,0 +1,4 @@
+pub mod board;
+pub mod game;
+pub mod pdn;
+pub mod server;
@@ -1,5 +1,47 @@
-mod board;
+use std::env;
+use std::ffi::OsString;
+use std::fmt::Display;
+use std::sync::Arc;
 
-fn main() {
-    println!("Hello, world!");
+use backend::board::book::OpeningBook;
+use backend::board::difficulty::EngineContext;
+use backend::board::evaluation::Weights;
+use backend::board::tablebase::Tablebase;
+use backend::server::{self, store::Store};
+
+const ADDRESS: ([u8; 4], u16) = ([127, 0, 0, 1], 3030);
+
+/// variable naming the JSON or TOML file of the weights the engine evaluates with
+const WEIGHTS: &str = "WEIGHTS";
+/// variable naming the opening book the engine plays from
+const BOOK: &str = "BOOK";
+/// variable naming the endgame tablebase file the engine plays and the server analyses with
+const TABLEBASE: &str = "TABLEBASE";
+
+/// reads the file named by the environment `variable` with `load`,
+/// `None` if the variable is not set or the file can not be read
+fn load_from<T, E, F>(variable: &str, load: F) -> Option<T>
+where
+    E: Display,
+    F: FnOnce(OsString) -> Result<T, E>,
+{
+    let path = env::var_os(variable)?;
+    match load(path.clone()) {
+        Ok(loaded) => Some(loaded),
+        Err(error) => {
+            eprintln!("{:?}: {}", path, error);
+            None
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let engine = EngineContext {
+        weights: load_from(WEIGHTS, Weights::load).unwrap_or_default(),
+        book: load_from(BOOK, OpeningBook::load).map(Arc::new),
+        tablebase: load_from(TABLEBASE, Tablebase::load).map(Arc::new),
+    };
+    let store = Store::with_engine(engine);
+    warp::serve(server::routes(store)).run(ADDRESS).await;
 }
@@ -0,0 +1,880 @@
+//! Portable Draughts Notation: tag pairs, then move text like
+//! `1. 32-28 19-23 2. 28x19 14x23 {a comment} (2... 13x24) 2-0`
+
+use std::error::Error;
+use std::fmt;
+
+use crate::board::fen::FenError;
+use crate::board::piece::Color;
+use crate::board::position::Position;
+use crate::board::rules::GameType;
+use crate::board::status::GameStatus;
+use crate::board::turn::Move;
+use crate::board::Board;
+use crate::game::Game;
+
+const RESULTS: [&str; 8] = ["2-0", "0-2", "1-1", "1-0", "0-1", "1/2-1/2", "0-0", "*"];
+const LINE_WIDTH: usize = 80;
+
+/// Reasons a PDN text can not be read or written
+#[derive(Debug, Clone, PartialEq)]
+pub enum PdnError {
+    /// the text does not follow the PDN grammar
+    Syntax(String),
+    /// the `FEN` tag holds no valid position
+    Fen(FenError),
+    /// the `GameType` tag names rules the board can not be played by
+    UnsupportedGameType(String),
+    /// the move can not be played in the position, `ply` counts moves of both sides from 1
+    IllegalMove { ply: usize, notation: String },
+    /// several legal moves fit the notation
+    AmbiguousMove { ply: usize, notation: String },
+}
+
+impl fmt::Display for PdnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PdnError::Syntax(message) => write!(f, "syntax error: {}", message),
+            PdnError::Fen(error) => write!(f, "invalid FEN tag: {}", error),
+            PdnError::UnsupportedGameType(game_type) => {
+                write!(f, "unsupported game type `{}`", game_type)
+            }
+            PdnError::IllegalMove { ply, notation } => {
+                write!(f, "illegal move `{}` at ply {}", notation, ply)
+            }
+            PdnError::AmbiguousMove { ply, notation } => {
+                write!(f, "ambiguous move `{}` at ply {}", notation, ply)
+            }
+        }
+    }
+}
+
+impl Error for PdnError {}
+
+impl From<FenError> for PdnError {
+    fn from(error: FenError) -> Self {
+        PdnError::Fen(error)
+    }
+}
+
+/// Move of a PDN game together with the annotations written after it
+#[derive(Debug, Clone)]
+pub struct PdnMove {
+    pub turn: Move,
+    pub comment: Option<String>,
+    /// lines played instead of this move
+    pub variations: Vec<Variation>,
+}
+
+impl From<Move> for PdnMove {
+    fn from(turn: Move) -> Self {
+        Self {
+            turn,
+            comment: None,
+            variations: Vec::new(),
+        }
+    }
+}
+
+/// Alternative line of moves
+#[derive(Debug, Clone, Default)]
+pub struct Variation {
+    /// comment written before the first move of the line
+    pub comment: Option<String>,
+    pub moves: Vec<PdnMove>,
+}
+
+/// Game record read from or written to PDN
+#[derive(Debug, Clone)]
+pub struct PdnGame {
+    /// tag pairs in the order they were written, e.g. `("White", "Sijbrands")`
+    pub tags: Vec<(String, String)>,
+    /// comment written before the first move
+    pub comment: Option<String>,
+    pub moves: Vec<PdnMove>,
+    /// game termination marker: `2-0`, `0-2`, `1-1` or `*` for an unfinished game
+    pub result: String,
+}
+
+impl PdnGame {
+    /// reads a text holding exactly one game
+    pub fn parse(text: &str) -> Result<Self, PdnError> {
+        let mut games = parse(text)?;
+        if games.len() != 1 {
+            return Err(PdnError::Syntax(format!(
+                "expected one game, found {}",
+                games.len()
+            )));
+        }
+        Ok(games.remove(0))
+    }
+
+    /// returns the value of the tag `name`
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// returns the rules named by the `GameType` tag, international draughts if there is none.
+    /// Only the number of the tag is read, the board description that may follow it is not.
+    pub fn game_type(&self) -> Result<GameType, PdnError> {
+        let tag = match self.tag("GameType") {
+            Some(tag) => tag,
+            None => return Ok(GameType::International),
+        };
+        tag.split(',')
+            .next()
+            .and_then(|number| number.trim().parse().ok())
+            .and_then(GameType::from_pdn_number)
+            .ok_or_else(|| PdnError::UnsupportedGameType(tag.to_string()))
+    }
+
+    /// returns the starting position of the game and the side to move in it,
+    /// taken from the `FEN` tag or the initial position of the game type if there is none
+    pub fn start(&self) -> Result<(Board, Color), PdnError> {
+        let game_type = self.game_type()?;
+        match self.tag("FEN") {
+            Some(fen) => Ok(Board::from_fen_for(fen, game_type)?),
+            None => Ok((Board::initial(game_type), game_type.rules().first)),
+        }
+    }
+
+    /// writes the game as PDN, move text wrapped at 80 characters
+    pub fn to_pdn(&self) -> Result<String, PdnError> {
+        let mut text = String::new();
+        for (name, value) in &self.tags {
+            text += &format!("[{} \"{}\"]\n", name, escape(value));
+        }
+        if !self.tags.is_empty() {
+            text.push('\n');
+        }
+
+        let (board, turn) = self.start()?;
+        let mut words = Vec::new();
+        if let Some(comment) = &self.comment {
+            words.push(format!("{{{}}}", comment));
+        }
+        let mut writer = Writer {
+            first_turn: turn,
+            opener: board.rules().first,
+        };
+        writer.line(&self.moves, board, turn, 0, &mut words)?;
+        words.push(self.result.clone());
+
+        let mut line = String::new();
+        for word in words {
+            if !line.is_empty() && line.len() + 1 + word.len() > LINE_WIDTH {
+                text += &line;
+                text.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line += &word;
+        }
+        text += &line;
+        text.push('\n');
+
+        Ok(text)
+    }
+
+    /// returns the `Game` played along the main line, positioned after its last move
+    pub fn to_game(&self) -> Result<Game, PdnError> {
+        let (board, turn) = self.start()?;
+        let size = board.size();
+        let mut game = Game::from_position(board, turn);
+        for (ply, pdn_move) in self.moves.iter().enumerate() {
+            game.play(&pdn_move.turn)
+                .map_err(|_| PdnError::IllegalMove {
+                    ply: ply + 1,
+                    notation: pdn_move.turn.notation(size),
+                })?;
+        }
+        Ok(game)
+    }
+}
+
+/// Record of every move of the game, with the `FEN` tag when it did not start
+/// from the initial position and the result taken from its status
+impl From<&Game> for PdnGame {
+    fn from(game: &Game) -> Self {
+        let game_type = game.game_type();
+        let mut tags = vec![("GameType".to_string(), game_type.pdn_number().to_string())];
+        let (board, turn) = game.start();
+        if *board != Board::initial(game_type) || turn != game_type.rules().first {
+            tags.push(("FEN".to_string(), board.to_fen(turn)));
+        }
+
+        let mut end = game.clone();
+        end.goto(game.moves().len())
+            .expect("the last ply can always be reached");
+        let [white_wins, black_wins, drawn] = results(game_type);
+        let result = match end.status() {
+            GameStatus::Ongoing => "*",
+            GameStatus::Won {
+                winner: Color::White,
+            } => white_wins,
+            GameStatus::Won { .. } => black_wins,
+            GameStatus::Drawn { .. } => drawn,
+        };
+        tags.push(("Result".to_string(), result.to_string()));
+
+        Self {
+            tags,
+            comment: None,
+            moves: game.moves().iter().cloned().map(PdnMove::from).collect(),
+            result: result.to_string(),
+        }
+    }
+}
+
+/// returns the results of a white win, a black win and a draw in `game_type`:
+/// American checkers score a game one point, the other game types two
+fn results(game_type: GameType) -> [&'static str; 3] {
+    match game_type {
+        GameType::English | GameType::Pool => ["1-0", "0-1", "1/2-1/2"],
+        _ => ["2-0", "0-2", "1-1"],
+    }
+}
+
+/// reads every game of a PDN text, checking each move against the board
+pub fn parse(text: &str) -> Result<Vec<PdnGame>, PdnError> {
+    parse_each(text)?.into_iter().collect()
+}
+
+/// reads every game of a PDN text like `parse` does, but a game that can not be played
+/// on the board only gives its own error, the games after it being read still.
+/// The whole text fails only when it does not follow the PDN grammar.
+pub fn parse_each(text: &str) -> Result<Vec<Result<PdnGame, PdnError>>, PdnError> {
+    let mut parser = Parser {
+        tokens: tokenize(text)?,
+        index: 0,
+    };
+    let mut games = Vec::new();
+    while parser.peek().is_some() {
+        match parser.game() {
+            Err(error @ PdnError::Syntax(_)) => return Err(error),
+            Err(error) => {
+                parser.skip_game();
+                games.push(Err(error));
+            }
+            Ok(game) => games.push(Ok(game)),
+        }
+    }
+    Ok(games)
+}
+
+/// writes the games one after another, separated with an empty line
+pub fn write(games: &[PdnGame]) -> Result<String, PdnError> {
+    let games = games
+        .iter()
+        .map(PdnGame::to_pdn)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(games.join("\n"))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Tag(String, String),
+    Comment(String),
+    Open,
+    Close,
+    Number,
+    Move(String),
+    Result(String),
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, PdnError> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+
+    while index < chars.len() {
+        match chars[index] {
+            c if c.is_whitespace() => index += 1,
+            '[' => {
+                let end = find(&chars, index, ']')?;
+                tokens.push(parse_tag(&chars[index + 1..end])?);
+                index = end + 1;
+            }
+            '{' => {
+                let end = find(&chars, index, '}')?;
+                let comment: String = chars[index + 1..end].iter().collect();
+                tokens.push(Token::Comment(comment.trim().to_string()));
+                index = end + 1;
+            }
+            ';' => {
+                let end = find(&chars, index, '\n').unwrap_or(chars.len());
+                let comment: String = chars[index + 1..end].iter().collect();
+                tokens.push(Token::Comment(comment.trim().to_string()));
+                index = end;
+            }
+            '(' => {
+                tokens.push(Token::Open);
+                index += 1;
+            }
+            ')' => {
+                tokens.push(Token::Close);
+                index += 1;
+            }
+            _ => {
+                let end = chars[index..]
+                    .iter()
+                    .position(|&c| c.is_whitespace() || "[]{}();".contains(c))
+                    .map_or(chars.len(), |length| index + length);
+                let word: String = chars[index..end].iter().collect();
+                classify(&word, &mut tokens)?;
+                index = end;
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// returns the index of the first `wanted` character after `start`
+fn find(chars: &[char], start: usize, wanted: char) -> Result<usize, PdnError> {
+    let mut quoted = false;
+    for (index, &c) in chars.iter().enumerate().skip(start + 1) {
+        match c {
+            '"' if wanted == ']' && chars[index - 1] != '\\' => quoted = !quoted,
+            c if c == wanted && !quoted => return Ok(index),
+            _ => {}
+        }
+    }
+    Err(PdnError::Syntax(format!("missing `{}`", wanted)))
+}
+
+/// reads the inside of a tag pair: `Name "value"`
+fn parse_tag(chars: &[char]) -> Result<Token, PdnError> {
+    let tag: String = chars.iter().collect();
+    let invalid = || PdnError::Syntax(format!("invalid tag `[{}]`", tag));
+
+    let tag = tag.trim();
+    let space = tag.find(char::is_whitespace).ok_or_else(invalid)?;
+    let (name, value) = tag.split_at(space);
+    let value = value.trim();
+    if !value.starts_with('"') || !value.ends_with('"') || value.len() < 2 {
+        return Err(invalid());
+    }
+    let value = value[1..value.len() - 1]
+        .replace("\\\"", "\"")
+        .replace("\\\\", "\\");
+    Ok(Token::Tag(name.to_string(), value))
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// sorts out a word of the move text: a move number like `12.` or `12...`,
+/// a move like `32-28` or `28x19x10`, possibly glued to its number, or a result
+fn classify(word: &str, tokens: &mut Vec<Token>) -> Result<(), PdnError> {
+    if RESULTS.contains(&word) {
+        tokens.push(Token::Result(word.to_string()));
+        return Ok(());
+    }
+
+    let mut word = word;
+    if let Some(dot) = word.find('.') {
+        if word[..dot].is_empty() || !word[..dot].chars().all(|c| c.is_ascii_digit()) {
+            return Err(PdnError::Syntax(format!("invalid move number `{}`", word)));
+        }
+        tokens.push(Token::Number);
+        word = word[dot..].trim_start_matches('.');
+        if word.is_empty() {
+            return Ok(());
+        }
+    }
+
+    let notation = word.trim_end_matches(['!', '?']);
+    let squares: Vec<&str> = notation.split(['-', 'x']).collect();
+    let valid = squares.len() >= 2
+        && squares
+            .iter()
+            .all(|square| !square.is_empty() && square.chars().all(|c| c.is_ascii_digit()));
+    if !valid {
+        return Err(PdnError::Syntax(format!("unexpected `{}`", word)));
+    }
+
+    tokens.push(Token::Move(notation.to_string()));
+    Ok(())
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    index: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index)
+    }
+
+    fn game(&mut self) -> Result<PdnGame, PdnError> {
+        let mut game = PdnGame {
+            tags: Vec::new(),
+            comment: None,
+            moves: Vec::new(),
+            result: "*".to_string(),
+        };
+        while let Some(Token::Tag(name, value)) = self.peek() {
+            game.tags.push((name.clone(), value.clone()));
+            self.index += 1;
+        }
+
+        let (mut board, mut turn) = game.start()?;
+        game.moves = self.line(&mut board, &mut turn, &mut game.comment, 0)?;
+
+        match self.peek() {
+            Some(Token::Result(result)) => {
+                game.result = result.clone();
+                self.index += 1;
+            }
+            Some(Token::Close) => return Err(PdnError::Syntax("unexpected `)`".to_string())),
+            _ => {}
+        }
+        Ok(game)
+    }
+
+    /// skips the rest of a game given up on after its tags: up to its result,
+    /// or up to the tags of the next game if it has none
+    fn skip_game(&mut self) {
+        while let Some(token) = self.peek() {
+            match token {
+                Token::Tag(..) => return,
+                Token::Result(_) => {
+                    self.index += 1;
+                    return;
+                }
+                _ => self.index += 1,
+            }
+        }
+    }
+
+    /// reads moves played from `board` until the end of the line,
+    /// which is a closing parenthesis, a result, a tag or the end of the text
+    fn line(
+        &mut self,
+        board: &mut Board,
+        turn: &mut Color,
+        comment: &mut Option<String>,
+        mut ply: usize,
+    ) -> Result<Vec<PdnMove>, PdnError> {
+        let mut moves: Vec<PdnMove> = Vec::new();
+        let mut before: Option<(Board, Color, usize)> = None;
+
+        loop {
+            match self.peek().cloned() {
+                Some(Token::Number) => self.index += 1,
+                Some(Token::Comment(text)) => {
+                    self.index += 1;
+                    let target = match moves.last_mut() {
+                        Some(last) => &mut last.comment,
+                        None => &mut *comment,
+                    };
+                    *target = Some(match target.take() {
+                        Some(previous) => format!("{} {}", previous, text),
+                        None => text,
+                    });
+                }
+                Some(Token::Move(notation)) => {
+                    self.index += 1;
+                    ply += 1;
+                    let played = resolve(&notation, &board.legal_moves(*turn), ply, board.size())?;
+                    before = Some((board.clone(), *turn, ply - 1));
+                    board.apply(&played);
+                    *turn = turn.opposite();
+                    moves.push(PdnMove::from(played));
+                }
+                Some(Token::Open) => {
+                    self.index += 1;
+                    let (mut board, mut turn, ply) = before.clone().ok_or_else(|| {
+                        PdnError::Syntax("variation before the first move".to_string())
+                    })?;
+                    let mut variation = Variation::default();
+                    variation.moves =
+                        self.line(&mut board, &mut turn, &mut variation.comment, ply)?;
+                    if self.peek() != Some(&Token::Close) {
+                        return Err(PdnError::Syntax("unclosed variation".to_string()));
+                    }
+                    self.index += 1;
+                    if let Some(last) = moves.last_mut() {
+                        last.variations.push(variation);
+                    }
+                }
+                _ => return Ok(moves),
+            }
+        }
+    }
+}
+
+/// finds the legal move written as `notation`, squares being numbered on a board of `size`
+/// cells a side. Intermediate squares of a capture, like the `19` of `28x19x10`,
+/// need to be given only to tell apart captures between the same squares.
+fn resolve(notation: &str, legal: &[Move], ply: usize, size: usize) -> Result<Move, PdnError> {
+    let illegal = || PdnError::IllegalMove {
+        ply,
+        notation: notation.to_string(),
+    };
+    let positions = notation
+        .split(['-', 'x'])
+        .map(|square| Position::from_square_on(square.parse().ok()?, size).ok())
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(illegal)?;
+    let from = positions[0];
+    let to = positions[positions.len() - 1];
+    let through = &positions[1..positions.len() - 1];
+
+    let candidates: Vec<&Move> = legal
+        .iter()
+        .filter(|turn| turn.starting_position == from && turn.end_position == to)
+        .filter(|turn| {
+            let mut path = turn.path.iter();
+            through.iter().all(|square| path.any(|cell| cell == square))
+        })
+        .collect();
+
+    match candidates.as_slice() {
+        [] => Err(illegal()),
+        [first, others @ ..] if others.iter().all(|other| other.is_equivalent(first)) => {
+            Ok((*first).clone())
+        }
+        _ => Err(PdnError::AmbiguousMove {
+            ply,
+            notation: notation.to_string(),
+        }),
+    }
+}
+
+/// returns the notation of `turn` on a board of `size` cells a side; intermediate squares
+/// are written only when another legal capture goes between the same squares
+fn notation(turn: &Move, legal: &[Move], size: usize) -> String {
+    let ambiguous = legal.iter().any(|other| {
+        other.starting_position == turn.starting_position
+            && other.end_position == turn.end_position
+            && !other.is_equivalent(turn)
+    });
+    if turn.kills.is_empty() || ambiguous {
+        return turn.notation(size);
+    }
+    let mut short = turn.clone();
+    short.path.clear();
+    short.notation(size)
+}
+
+struct Writer {
+    first_turn: Color,
+    /// side making the first move of the game type, whose moves get the move numbers
+    opener: Color,
+}
+
+impl Writer {
+    fn line(
+        &mut self,
+        moves: &[PdnMove],
+        mut board: Board,
+        mut turn: Color,
+        mut ply: usize,
+        words: &mut Vec<String>,
+    ) -> Result<(), PdnError> {
+        let mut numbered = false;
+        for pdn_move in moves {
+            let offset = if self.first_turn != self.opener { 1 } else { 0 };
+            let number = (ply + offset) / 2 + 1;
+            if turn == self.opener {
+                words.push(format!("{}.", number));
+            } else if !numbered {
+                words.push(format!("{}...", number));
+            }
+            numbered = true;
+
+            let legal = board.legal_moves(turn);
+            if !legal
+                .iter()
+                .any(|other| other.is_equivalent(&pdn_move.turn))
+            {
+                return Err(PdnError::IllegalMove {
+                    ply: ply + 1,
+                    notation: pdn_move.turn.notation(board.size()),
+                });
+            }
+            words.push(notation(&pdn_move.turn, &legal, board.size()));
+
+            if let Some(comment) = &pdn_move.comment {
+                words.push(format!("{{{}}}", comment));
+                numbered = false;
+            }
+            for variation in &pdn_move.variations {
+                let mut inner = Vec::new();
+                if let Some(comment) = &variation.comment {
+                    inner.push(format!("{{{}}}", comment));
+                }
+                self.line(&variation.moves, board.clone(), turn, ply, &mut inner)?;
+                match inner.first_mut() {
+                    Some(first) => *first = format!("({}", first),
+                    None => inner.push("(".to_string()),
+                }
+                if let Some(last) = inner.last_mut() {
+                    last.push(')');
+                }
+                words.extend(inner);
+                numbered = false;
+            }
+
+            board.apply(&pdn_move.turn);
+            turn = turn.opposite();
+            ply += 1;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+const SAMPLE: &str = r#"[Event "Club championship"]
+[White "Alice"]
+[Black "Bob \"the wall\""]
+[Result "1-1"]
+[GameType "20"]
+
+1. 32-28 19-23 2. 28x19 14x23 {the classical exchange} 3. 37-32 (3. 31-27 10-14)
+10-14 1-1
+"#;
+
+#[test]
+fn test_parse() {
+    let game = PdnGame::parse(SAMPLE).unwrap();
+    assert_eq!(game.tags.len(), 5);
+    assert_eq!(game.tag("White"), Some("Alice"));
+    assert_eq!(game.tag("Black"), Some("Bob \"the wall\""));
+    assert_eq!(game.result, "1-1");
+    assert_eq!(game.moves.len(), 6);
+
+    let capture = &game.moves[2].turn;
+    assert_eq!(capture.starting_position.square(), Ok(28));
+    assert_eq!(capture.end_position.square(), Ok(19));
+    assert_eq!(capture.kills.len(), 1);
+    assert_eq!(
+        game.moves[3].comment.as_deref(),
+        Some("the classical exchange")
+    );
+    assert_eq!(game.moves[4].variations.len(), 1);
+    assert_eq!(game.moves[4].variations[0].moves.len(), 2);
+}
+
+#[test]
+fn test_round_trip() {
+    let game = PdnGame::parse(SAMPLE).unwrap();
+    let written = game.to_pdn().unwrap();
+    assert_eq!(
+        written,
+        r#"[Event "Club championship"]
+[White "Alice"]
+[Black "Bob \"the wall\""]
+[Result "1-1"]
+[GameType "20"]
+
+1. 32-28 19-23 2. 28x19 14x23 {the classical exchange} 3. 37-32 (3. 31-27 10-14)
+3... 10-14 1-1
+"#
+    );
+    let again = PdnGame::parse(&written).unwrap();
+    assert_eq!(again.to_pdn().unwrap(), written);
+}
+
+#[test]
+fn test_several_games() {
+    let text = format!("{}\n[Event \"Second\"]\n1.32-28 *\n", SAMPLE);
+    let games = parse(&text).unwrap();
+    assert_eq!(games.len(), 2);
+    assert_eq!(games[1].tag("Event"), Some("Second"));
+    assert_eq!(games[1].moves.len(), 1);
+    assert_eq!(games[1].result, "*");
+    assert_eq!(
+        write(&games).unwrap(),
+        format!(
+            "{}\n{}",
+            games[0].to_pdn().unwrap(),
+            "[Event \"Second\"]\n\n1. 32-28 *\n"
+        )
+    );
+}
+
+#[test]
+fn test_parse_each() {
+    let text = "1. 32-28 19-23 2-0\n\n\
+                [Event \"illegal\"]\n1. 32-28 32-28 (1... 19-23) 2. 28-23 *\n\n\
+                [FEN \"W:W99:B1\"]\n1. 32-28 *\n\n\
+                1. 33-28 *";
+    let games = parse_each(text).unwrap();
+    assert_eq!(games.len(), 4);
+    assert_eq!(games[0].as_ref().unwrap().result, "2-0");
+    assert!(matches!(
+        games[1],
+        Err(PdnError::IllegalMove { ply: 2, .. })
+    ));
+    assert!(matches!(games[2], Err(PdnError::Fen(_))));
+    assert_eq!(games[3].as_ref().unwrap().moves.len(), 1);
+    assert!(matches!(
+        parse(text),
+        Err(PdnError::IllegalMove { ply: 2, .. })
+    ));
+
+    assert!(matches!(
+        parse_each("1. 32-28 ) *"),
+        Err(PdnError::Syntax(_))
+    ));
+}
+
+#[test]
+fn test_fen_start() {
+    let text = r#"[FEN "B:W28,K46:B19"]
+1... 19-23 ; black steps in
+2. 28x19"#;
+    let game = PdnGame::parse(text).unwrap();
+    assert_eq!(game.moves.len(), 2);
+    assert_eq!(game.moves[0].comment.as_deref(), Some("black steps in"));
+    assert!(game
+        .to_pdn()
+        .unwrap()
+        .contains("1... 19-23 {black steps in} 2. 28x19"));
+}
+
+#[test]
+fn test_intermediate_squares() {
+    let text = r#"[FEN "W:W48:B42,32"]
+1. 48x37x28"#;
+    let game = PdnGame::parse(text).unwrap();
+    assert_eq!(game.moves[0].turn.kills.len(), 2);
+    assert!(game.to_pdn().unwrap().contains("1. 48x28"));
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(
+        PdnGame::parse("1. 32-28 19-24 2. 31-26 24-35").unwrap_err(),
+        PdnError::IllegalMove {
+            ply: 4,
+            notation: "24-35".to_string()
+        }
+    );
+    assert_eq!(
+        PdnGame::parse("1. 32-28 19-23 2. 33-29").unwrap_err(),
+        PdnError::IllegalMove {
+            ply: 3,
+            notation: "33-29".to_string()
+        }
+    );
+    assert_eq!(
+        PdnGame::parse("[GameType \"22\"]\n1. 11-15").unwrap_err(),
+        PdnError::UnsupportedGameType("22".to_string())
+    );
+    assert!(matches!(
+        PdnGame::parse("1. 32-28 (1. 33-29"),
+        Err(PdnError::Syntax(_))
+    ));
+    assert!(matches!(
+        PdnGame::parse("1. 32-28 hello"),
+        Err(PdnError::Syntax(_))
+    ));
+    assert!(matches!(
+        PdnGame::parse("[FEN \"W:W60\"]"),
+        Err(PdnError::Fen(_))
+    ));
+}
+
+#[test]
+fn test_game_record() {
+    let game = PdnGame::parse(SAMPLE).unwrap().to_game().unwrap();
+    assert_eq!(game.ply(), 6);
+    assert_eq!(game.turn(), Color::White);
+
+    let record = PdnGame::from(&game);
+    assert_eq!(record.tag("GameType"), Some("20"));
+    assert_eq!(record.tag("FEN"), None);
+    assert_eq!(record.result, "*");
+    assert_eq!(
+        record.to_pdn().unwrap(),
+        "[GameType \"20\"]\n[Result \"*\"]\n\n1. 32-28 19-23 2. 28x19 14x23 3. 37-32 10-14 *\n"
+    );
+
+    let (board, turn) = Board::from_fen("W:W28:B23").unwrap();
+    let mut game = Game::from_position(board, turn);
+    let capture = game.legal_moves().remove(0);
+    game.play(&capture).unwrap();
+    let record = PdnGame::from(&game);
+    assert_eq!(record.tag("FEN"), Some("W:W28:B23"));
+    assert_eq!(record.result, "2-0");
+    assert_eq!(record.to_game().unwrap().moves().len(), 1);
+}
+
+#[test]
+fn test_english_game() {
+    let text = "[GameType \"21\"]\n\n1. 11-15 22-18 2. 15x22 25x18 3. 8-11 *\n";
+    let game = PdnGame::parse(text).unwrap();
+    assert_eq!(game.game_type(), Ok(GameType::English));
+    assert_eq!(game.moves.len(), 5);
+    assert_eq!(game.moves[3].turn.kills.len(), 1);
+    assert_eq!(game.to_pdn().unwrap(), text);
+
+    let played = game.to_game().unwrap();
+    assert_eq!(played.game_type(), GameType::English);
+    assert_eq!(played.turn(), Color::White);
+    let record = PdnGame::from(&played);
+    assert_eq!(record.tag("GameType"), Some("21"));
+    assert_eq!(record.tag("FEN"), None);
+
+    let text = "[GameType \"21\"]\n[FEN \"W:W18:B14,15\"]\n1. 18x11 *";
+    let game = PdnGame::parse(text).unwrap();
+    assert!(game.to_pdn().unwrap().contains("1... 18x11 *"));
+}
+
+#[test]
+fn test_english_draw() {
+    let text = "[GameType \"21\"]\n[FEN \"B:WK32:BK1\"]\n\n\
+                1. 1-5 32-28 2. 5-1 28-32 3. 1-5 32-28 4. 5-1 28-32 *\n";
+    let game = PdnGame::parse(text).unwrap().to_game().unwrap();
+    assert!(matches!(game.status(), GameStatus::Drawn { .. }));
+
+    let record = PdnGame::from(&game);
+    assert_eq!(record.result, "1/2-1/2");
+    let written = record.to_pdn().unwrap();
+    assert!(written.ends_with(" 1/2-1/2\n"));
+    let read = PdnGame::parse(&written).unwrap();
+    assert_eq!(read.result, "1/2-1/2");
+    assert_eq!(read.tag("Result"), Some("1/2-1/2"));
+    assert_eq!(read.to_game().unwrap().moves(), game.moves());
+}
+
+#[test]
+fn test_russian_game() {
+    let text = "[GameType \"25\"]\n[FEN \"W:W11:B7,9\"]\n\n1. 11x2x13 *\n";
+    let game = PdnGame::parse(text).unwrap();
+    assert_eq!(game.game_type(), Ok(GameType::Russian));
+    assert_eq!(game.to_pdn().unwrap(), text.replace("11x2x13", "11x13"));
+    let played = game.to_game().unwrap();
+    assert_eq!(played.board().to_fen(played.turn()), "B:WK13:B");
+
+    let game = PdnGame::parse("[GameType \"26\"]\n1. 22-18 *").unwrap();
+    assert_eq!(game.to_game().unwrap().game_type(), GameType::Brazilian);
+}
+
+#[test]
+fn test_canadian_and_frisian_games() {
+    let game = PdnGame::parse("[GameType \"27\"]\n1. 43-37 30-36 *").unwrap();
+    let played = game.to_game().unwrap();
+    assert_eq!(played.game_type(), GameType::Canadian);
+    assert_eq!(played.board().size(), 12);
+    assert_eq!(played.moves().len(), 2);
+    assert_eq!(PdnGame::from(&played).tag("GameType"), Some("27"));
+
+    let text = "[GameType \"40\"]\n[FEN \"W:W28:B16,27\"]\n\n1. 28x26x6 *\n";
+    let game = PdnGame::parse(text).unwrap();
+    assert_eq!(game.game_type(), Ok(GameType::Frisian));
+    assert_eq!(game.to_pdn().unwrap(), text.replace("28x26x6", "28x6"));
+    let played = game.to_game().unwrap();
+    assert_eq!(played.board().to_fen(played.turn()), "B:W6:B");
+}
@@ -0,0 +1,86 @@
+use std::convert::Infallible;
+use std::fmt;
+
+use serde::Serialize;
+use warp::http::StatusCode;
+use warp::{reject, Rejection, Reply};
+
+/// Errors reported to the clients of the game API
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ApiError {
+    GameNotFound,
+    IllegalMove,
+    AmbiguousMove,
+    NotYourTurn,
+    GameOver,
+    Spectator,
+    UnknownPlayer,
+    InvalidMessage,
+}
+
+impl ApiError {
+    pub fn status(self) -> StatusCode {
+        match self {
+            ApiError::GameNotFound => StatusCode::NOT_FOUND,
+            ApiError::IllegalMove | ApiError::AmbiguousMove => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::NotYourTurn | ApiError::GameOver => StatusCode::CONFLICT,
+            ApiError::Spectator => StatusCode::FORBIDDEN,
+            ApiError::UnknownPlayer | ApiError::InvalidMessage => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            ApiError::GameNotFound => "game not found",
+            ApiError::IllegalMove => "illegal move",
+            ApiError::AmbiguousMove => "ambiguous move, list the captured pieces",
+            ApiError::NotYourTurn => "not your turn",
+            ApiError::GameOver => "the game is over",
+            ApiError::Spectator => "spectators can not move",
+            ApiError::UnknownPlayer => "player has to be white or black",
+            ApiError::InvalidMessage => "invalid message",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl reject::Reject for ApiError {}
+
+impl From<ApiError> for Rejection {
+    fn from(error: ApiError) -> Self {
+        reject::custom(error)
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: String,
+}
+
+/// turns rejections into JSON error responses
+pub async fn handle_rejection(rejection: Rejection) -> Result<impl Reply, Infallible> {
+    let (status, error) = if let Some(&error) = rejection.find::<ApiError>() {
+        (error.status(), error.to_string())
+    } else if rejection.is_not_found() {
+        (StatusCode::NOT_FOUND, "not found".to_string())
+    } else if let Some(error) = rejection.find::<warp::body::BodyDeserializeError>() {
+        (StatusCode::BAD_REQUEST, error.to_string())
+    } else if rejection.find::<reject::MethodNotAllowed>().is_some() {
+        (
+            StatusCode::METHOD_NOT_ALLOWED,
+            "method not allowed".to_string(),
+        )
+    } else {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "internal server error".to_string(),
+        )
+    };
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&ErrorBody { error }),
+        status,
+    ))
+}
@@ -0,0 +1,76 @@
+use warp::http::StatusCode;
+use warp::hyper::body::Bytes;
+use warp::ws::Ws;
+use warp::{Rejection, Reply};
+
+use crate::board::piece::Color;
+use crate::pdn::PdnGame;
+use crate::server::error::ApiError;
+use crate::server::live;
+use crate::server::store::Store;
+use crate::server::views::{parse_player, GameRequest, GameView, MoveRequest};
+
+pub async fn create_game(body: Bytes, store: Store) -> Result<impl Reply, Rejection> {
+    let request: GameRequest = if body.is_empty() {
+        GameRequest::default()
+    } else {
+        serde_json::from_slice(&body).map_err(|_| ApiError::InvalidMessage)?
+    };
+    let (id, game) = store.create(request.game_type, request.engine);
+    let view = store
+        .engine_reply(id)
+        .await?
+        .unwrap_or_else(|| GameView::new(id, &game));
+    Ok(warp::reply::with_status(
+        warp::reply::json(&view),
+        StatusCode::CREATED,
+    ))
+}
+
+pub async fn get_game(id: u64, store: Store) -> Result<impl Reply, Rejection> {
+    let view = store.with_game(id, |game| Ok(GameView::new(id, game)))?;
+    Ok(warp::reply::json(&view))
+}
+
+pub async fn legal_moves(id: u64, store: Store) -> Result<impl Reply, Rejection> {
+    let moves = store.with_game(id, |game| Ok(game.legal_moves()))?;
+    Ok(warp::reply::json(&moves))
+}
+
+pub async fn analyse(id: u64, store: Store) -> Result<impl Reply, Rejection> {
+    let analysis = store.analyse(id)?;
+    Ok(warp::reply::json(&analysis))
+}
+
+pub async fn export_pdn(id: u64, store: Store) -> Result<impl Reply, Rejection> {
+    let pdn = store.with_game(id, |game| {
+        Ok(PdnGame::from(&*game)
+            .to_pdn()
+            .expect("moves of a game are legal"))
+    })?;
+    Ok(pdn)
+}
+
+pub async fn play_move(
+    id: u64,
+    request: MoveRequest,
+    store: Store,
+) -> Result<impl Reply, Rejection> {
+    let view = store.play(id, &request, None)?;
+    let view = store.engine_reply(id).await?.unwrap_or(view);
+    Ok(warp::reply::json(&view))
+}
+
+pub async fn watch(id: u64, ws: Ws, store: Store) -> Result<impl Reply, Rejection> {
+    connect(id, None, ws, store)
+}
+
+pub async fn join(id: u64, player: String, ws: Ws, store: Store) -> Result<impl Reply, Rejection> {
+    let player = parse_player(&player)?;
+    connect(id, Some(player), ws, store)
+}
+
+fn connect(id: u64, player: Option<Color>, ws: Ws, store: Store) -> Result<impl Reply, Rejection> {
+    store.with_game(id, |_| Ok(()))?;
+    Ok(ws.on_upgrade(move |socket| live::session(socket, id, player, store)))
+}
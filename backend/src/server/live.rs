@@ -0,0 +1,55 @@
+use futures::{FutureExt, StreamExt};
+use tokio::sync::mpsc;
+use warp::ws::{Message, WebSocket};
+
+use crate::board::piece::Color;
+use crate::server::error::ApiError;
+use crate::server::store::Store;
+use crate::server::views::{Event, MoveRequest};
+
+/// runs the live channel of one client: pushes every event of the game to it and,
+/// if the client is a `player`, plays the moves it sends as `{"from": [x, y], "to": [x, y]}`
+pub async fn session(socket: WebSocket, id: u64, player: Option<Color>, store: Store) {
+    let (socket_tx, mut socket_rx) = socket.split();
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    let outgoing = rx.map(|event: Event| {
+        let text = serde_json::to_string(&event).expect("events always serialize");
+        Ok(Message::text(text))
+    });
+    tokio::spawn(outgoing.forward(socket_tx).map(|_| ()));
+
+    if store.subscribe(id, tx.clone()).is_err() {
+        return;
+    }
+
+    while let Some(Ok(message)) = socket_rx.next().await {
+        if message.is_close() {
+            break;
+        }
+        let text = match message.to_str() {
+            Ok(text) => text,
+            Err(_) => continue,
+        };
+
+        let played = match play(&store, id, player, text) {
+            Ok(()) => store.engine_reply(id).await.map(|_| ()),
+            error => error,
+        };
+        if let Err(error) = played {
+            let event = Event::Error {
+                error: error.to_string(),
+            };
+            if tx.send(event).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+fn play(store: &Store, id: u64, player: Option<Color>, text: &str) -> Result<(), ApiError> {
+    let player = player.ok_or(ApiError::Spectator)?;
+    let request: MoveRequest = serde_json::from_str(text).map_err(|_| ApiError::InvalidMessage)?;
+    store.play(id, &request, Some(player))?;
+    Ok(())
+}
@@ -0,0 +1,438 @@
+pub mod error;
+mod handlers;
+mod live;
+pub mod store;
+pub mod views;
+
+use warp::{Filter, Rejection, Reply};
+
+use crate::server::store::Store;
+
+/// every route of the game API:
+///
+/// - `POST /games` starts a new game of international draughts, of another game type
+///   with `{"game_type": "english"}`, the accepted names being `international`, `english`,
+///   `russian`, `brazilian`, `pool`, `canadian` and `frisian`, against the engine with
+///   `{"engine": {"color": "black", "difficulty": "easy"}}`
+/// - `GET /games/:id` returns the board, the side to move and the status of the game
+/// - `GET /games/:id/moves` lists the legal moves of the side to move
+/// - `GET /games/:id/analysis` looks the position up in the endgame tablebase
+/// - `GET /games/:id/pdn` exports the game in the Portable Draughts Notation
+/// - `POST /games/:id/moves` plays the move given as `{"from": [x, y], "to": [x, y]}`
+/// - `GET /games/:id/live` opens the WebSocket channel of the game for a spectator
+/// - `GET /games/:id/live/:player` opens it for the `white` or `black` player
+pub fn routes(store: Store) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    let create_game = warp::path!("games")
+        .and(warp::post())
+        .and(warp::body::bytes())
+        .and(with_store(store.clone()))
+        .and_then(handlers::create_game);
+
+    let get_game = warp::path!("games" / u64)
+        .and(warp::get())
+        .and(with_store(store.clone()))
+        .and_then(handlers::get_game);
+
+    let legal_moves = warp::path!("games" / u64 / "moves")
+        .and(warp::get())
+        .and(with_store(store.clone()))
+        .and_then(handlers::legal_moves);
+
+    let analyse = warp::path!("games" / u64 / "analysis")
+        .and(warp::get())
+        .and(with_store(store.clone()))
+        .and_then(handlers::analyse);
+
+    let export_pdn = warp::path!("games" / u64 / "pdn")
+        .and(warp::get())
+        .and(with_store(store.clone()))
+        .and_then(handlers::export_pdn);
+
+    let play_move = warp::path!("games" / u64 / "moves")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_store(store.clone()))
+        .and_then(handlers::play_move);
+
+    let watch = warp::path!("games" / u64 / "live")
+        .and(warp::ws())
+        .and(with_store(store.clone()))
+        .and_then(handlers::watch);
+
+    let join = warp::path!("games" / u64 / "live" / String)
+        .and(warp::ws())
+        .and(with_store(store))
+        .and_then(handlers::join);
+
+    create_game
+        .or(get_game)
+        .or(legal_moves)
+        .or(analyse)
+        .or(export_pdn)
+        .or(play_move)
+        .or(watch)
+        .or(join)
+        .recover(error::handle_rejection)
+        .with(warp::cors().allow_any_origin())
+}
+
+fn with_store(
+    store: Store,
+) -> impl Filter<Extract = (Store,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || store.clone())
+}
+
+#[cfg(test)]
+use crate::board::book::OpeningBook;
+#[cfg(test)]
+use crate::board::difficulty::{Difficulty, EngineContext};
+#[cfg(test)]
+use crate::board::piece::Color;
+#[cfg(test)]
+use crate::board::rules::GameType;
+#[cfg(test)]
+use crate::board::tablebase::Tablebase;
+#[cfg(test)]
+use crate::board::Board;
+#[cfg(test)]
+use crate::game::Game;
+#[cfg(test)]
+use crate::server::error::ApiError;
+#[cfg(test)]
+use crate::server::views::Engine;
+#[cfg(test)]
+use std::sync::Arc;
+#[cfg(test)]
+use warp::http::StatusCode;
+
+#[cfg(test)]
+async fn send(
+    store: &Store,
+    method: &str,
+    path: &str,
+    body: Option<&str>,
+) -> (StatusCode, serde_json::Value) {
+    let mut request = warp::test::request().method(method).path(path);
+    if let Some(body) = body {
+        request = request
+            .header("content-type", "application/json")
+            .body(body);
+    }
+    let response = request.reply(&routes(store.clone())).await;
+    let body = serde_json::from_slice(response.body()).unwrap();
+    (response.status(), body)
+}
+
+#[tokio::test]
+async fn test_create_and_get_game() {
+    let store = Store::default();
+    let (status, created) = send(&store, "POST", "/games", None).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(created["id"], 1);
+    assert_eq!(created["game_type"], "international");
+    assert_eq!(created["turn"], "white");
+    assert_eq!(created["board"][0][1], "BP");
+    assert_eq!(created["board"][6][1], "WP");
+    assert_eq!(created["board"][5][0], "--");
+
+    let (status, fetched) = send(&store, "GET", "/games/1", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(fetched, created);
+}
+
+#[tokio::test]
+async fn test_legal_moves() {
+    let store = Store::default();
+    send(&store, "POST", "/games", None).await;
+    let (status, moves) = send(&store, "GET", "/games/1/moves", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(moves.as_array().unwrap().len(), 9);
+    assert_eq!(moves[0]["kills"], serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn test_play_move() {
+    let store = Store::default();
+    send(&store, "POST", "/games", None).await;
+    let body = r#"{"from": [1, 6], "to": [0, 5]}"#;
+    let (status, game) = send(&store, "POST", "/games/1/moves", Some(body)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(game["turn"], "black");
+    assert_eq!(game["board"][6][1], "--");
+    assert_eq!(game["board"][5][0], "WP");
+}
+
+#[tokio::test]
+async fn test_export_pdn() {
+    let store = Store::default();
+    send(&store, "POST", "/games", None).await;
+    let body = r#"{"from": [3, 6], "to": [4, 5]}"#;
+    send(&store, "POST", "/games/1/moves", Some(body)).await;
+
+    let response = warp::test::request()
+        .method("GET")
+        .path("/games/1/pdn")
+        .reply(&routes(store))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.body(),
+        "[GameType \"20\"]\n[Result \"*\"]\n\n1. 32-28 *\n"
+    );
+}
+
+#[tokio::test]
+async fn test_english_game() {
+    let store = Store::default();
+    let body = r#"{"game_type": "english"}"#;
+    let (status, created) = send(&store, "POST", "/games", Some(body)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(created["game_type"], "english");
+    assert_eq!(created["turn"], "black");
+    assert_eq!(created["board"].as_array().unwrap().len(), 8);
+    let (_, moves) = send(&store, "GET", "/games/1/moves", None).await;
+    assert_eq!(moves.as_array().unwrap().len(), 7);
+
+    let body = r#"{"from": [5, 2], "to": [4, 3]}"#;
+    let (status, game) = send(&store, "POST", "/games/1/moves", Some(body)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(game["turn"], "white");
+
+    let response = warp::test::request()
+        .method("GET")
+        .path("/games/1/pdn")
+        .reply(&routes(store))
+        .await;
+    assert_eq!(
+        response.body(),
+        "[GameType \"21\"]\n[Result \"*\"]\n\n1. 11-15 *\n"
+    );
+}
+
+#[tokio::test]
+async fn test_engine_opponent() {
+    let store = Store::default();
+    let body = r#"{"engine": {"color": "black", "difficulty": "easy"}}"#;
+    let (status, created) = send(&store, "POST", "/games", Some(body)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(created["turn"], "white");
+
+    let body = r#"{"from": [1, 6], "to": [0, 5]}"#;
+    let (status, game) = send(&store, "POST", "/games/1/moves", Some(body)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(game["turn"], "white");
+    assert_eq!(store.with_game(1, |game| Ok(game.ply())).unwrap(), 2);
+
+    let body = r#"{"engine": {"color": "white", "difficulty": "medium"}}"#;
+    let (status, created) = send(&store, "POST", "/games", Some(body)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(created["turn"], "black");
+
+    // nobody can move for the engine, even while it is still thinking
+    let engine = Engine {
+        color: Color::White,
+        difficulty: Difficulty::Easy,
+    };
+    store.create(GameType::International, Some(engine));
+    let body = r#"{"from": [1, 6], "to": [0, 5]}"#;
+    let (status, error) = send(&store, "POST", "/games/3/moves", Some(body)).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(error["error"], "not your turn");
+    let request = serde_json::from_str(body).unwrap();
+    assert_eq!(
+        store.play(3, &request, Some(Color::White)).unwrap_err(),
+        ApiError::NotYourTurn
+    );
+    assert_eq!(store.with_game(3, |game| Ok(game.ply())).unwrap(), 0);
+
+    let body = r#"{"engine": {"color": "white", "difficulty": "impossible"}}"#;
+    let (status, error) = send(&store, "POST", "/games", Some(body)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(error["error"], "invalid message");
+}
+
+#[tokio::test]
+async fn test_engine_book() {
+    let hash = Board::new().position_hash(Color::White);
+    let book = OpeningBook::parse(&format!("{:016x} 31-26 1", hash)).unwrap();
+    let store = Store::with_engine(EngineContext {
+        book: Some(Arc::new(book)),
+        ..EngineContext::default()
+    });
+    // even the easiest engine, which plays nearly any move, sticks to the book
+    let body = r#"{"engine": {"color": "white", "difficulty": "easy"}}"#;
+    for _ in 0..5 {
+        let (status, created) = send(&store, "POST", "/games", Some(body)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(created["turn"], "black");
+        assert_eq!(created["board"][6][1], "--");
+        assert_eq!(created["board"][5][0], "WP");
+    }
+}
+
+#[tokio::test]
+async fn test_analysis() {
+    let store = Store::default();
+    send(&store, "POST", "/games", None).await;
+    let (status, analysis) = send(&store, "GET", "/games/1/analysis", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(analysis, serde_json::json!({ "tablebase": null }));
+
+    let store = Store::with_engine(EngineContext {
+        tablebase: Some(Arc::new(Tablebase::generate(2))),
+        ..EngineContext::default()
+    });
+    send(&store, "POST", "/games", None).await;
+    store
+        .with_game(1, |game| {
+            let (board, turn) = Board::from_fen("W:W28:B23").unwrap();
+            *game = Game::from_position(board, turn);
+            Ok(())
+        })
+        .unwrap();
+    let (_, analysis) = send(&store, "GET", "/games/1/analysis", None).await;
+    assert_eq!(
+        analysis,
+        serde_json::json!({"tablebase": {"result": "win", "plies": 1}})
+    );
+}
+
+#[tokio::test]
+async fn test_errors() {
+    let store = Store::default();
+    send(&store, "POST", "/games", None).await;
+
+    let (status, error) = send(&store, "GET", "/games/7", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(error["error"], "game not found");
+
+    let body = r#"{"from": [1, 6], "to": [1, 5]}"#;
+    let (status, error) = send(&store, "POST", "/games/1/moves", Some(body)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(error["error"], "illegal move");
+
+    let body = r#"{"from": [1, 3], "to": [0, 4]}"#;
+    let (status, _) = send(&store, "POST", "/games/1/moves", Some(body)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let (status, _) = send(&store, "POST", "/games/1/moves", Some("{}")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_game_over() {
+    let store = Store::default();
+    send(&store, "POST", "/games", None).await;
+    let (status, game) = send(&store, "GET", "/games/1", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(game["status"], serde_json::json!({"state": "ongoing"}));
+
+    store
+        .with_game(1, |game| {
+            let (board, turn) = Board::from_fen("W:W28:B23").unwrap();
+            *game = Game::from_position(board, turn);
+            Ok(())
+        })
+        .unwrap();
+    let mut spectator = warp::test::ws()
+        .path("/games/1/live")
+        .handshake(routes(store.clone()))
+        .await
+        .unwrap();
+    receive(&mut spectator).await;
+
+    let body = r#"{"from": [4, 5], "to": [6, 3]}"#;
+    let (status, game) = send(&store, "POST", "/games/1/moves", Some(body)).await;
+    assert_eq!(status, StatusCode::OK);
+    let won = serde_json::json!({"state": "won", "winner": "white"});
+    assert_eq!(game["status"], won);
+    assert_eq!(receive(&mut spectator).await["type"], "move");
+    assert_eq!(receive(&mut spectator).await["type"], "board");
+    let event = receive(&mut spectator).await;
+    assert_eq!(event["type"], "game_over");
+    assert_eq!(event["status"], won);
+    assert_eq!(
+        store.with_game(1, |game| Ok(game.turn())).unwrap(),
+        Color::Black
+    );
+
+    let body = r#"{"from": [6, 3], "to": [5, 2]}"#;
+    let (status, error) = send(&store, "POST", "/games/1/moves", Some(body)).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(error["error"], "the game is over");
+}
+
+#[cfg(test)]
+async fn receive(client: &mut warp::test::WsClient) -> serde_json::Value {
+    let message = client.recv().await.unwrap();
+    serde_json::from_str(message.to_str().unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn test_live_channel() {
+    let store = Store::default();
+    send(&store, "POST", "/games", None).await;
+
+    let mut white = warp::test::ws()
+        .path("/games/1/live/white")
+        .handshake(routes(store.clone()))
+        .await
+        .unwrap();
+    let mut spectator = warp::test::ws()
+        .path("/games/1/live")
+        .handshake(routes(store.clone()))
+        .await
+        .unwrap();
+    assert_eq!(receive(&mut white).await["type"], "board");
+    assert_eq!(receive(&mut spectator).await["type"], "board");
+
+    white.send_text(r#"{"from": [1, 6], "to": [0, 5]}"#).await;
+    for client in &mut [&mut white, &mut spectator] {
+        let event = receive(client).await;
+        assert_eq!(event["type"], "move");
+        assert_eq!(event["move"]["to"], serde_json::json!([0, 5]));
+        let event = receive(client).await;
+        assert_eq!(event["type"], "board");
+        assert_eq!(event["game"]["turn"], "black");
+    }
+
+    white.send_text(r#"{"from": [0, 5], "to": [1, 4]}"#).await;
+    let event = receive(&mut white).await;
+    assert_eq!(event["type"], "error");
+    assert_eq!(event["error"], "not your turn");
+
+    spectator
+        .send_text(r#"{"from": [0, 3], "to": [1, 4]}"#)
+        .await;
+    assert_eq!(
+        receive(&mut spectator).await["error"],
+        "spectators can not move"
+    );
+}
+
+#[tokio::test]
+async fn test_live_channel_sees_http_moves() {
+    let store = Store::default();
+    send(&store, "POST", "/games", None).await;
+    let mut spectator = warp::test::ws()
+        .path("/games/1/live")
+        .handshake(routes(store.clone()))
+        .await
+        .unwrap();
+    receive(&mut spectator).await;
+
+    let body = r#"{"from": [1, 6], "to": [0, 5]}"#;
+    send(&store, "POST", "/games/1/moves", Some(body)).await;
+    assert_eq!(receive(&mut spectator).await["type"], "move");
+    assert_eq!(receive(&mut spectator).await["type"], "board");
+}
+
+#[tokio::test]
+async fn test_live_channel_of_unknown_game() {
+    let store = Store::default();
+    let result = warp::test::ws()
+        .path("/games/1/live")
+        .handshake(routes(store))
+        .await;
+    assert!(result.is_err());
+}
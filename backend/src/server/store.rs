@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::board::difficulty::EngineContext;
+use crate::board::piece::Color;
+use crate::board::rules::GameType;
+use crate::board::search::SearchPosition;
+use crate::board::turn::Move;
+use crate::game::Game;
+use tokio::sync::mpsc;
+
+use crate::server::error::ApiError;
+use crate::server::views::{AnalysisView, Engine, Event, GameView, MoveRequest};
+
+/// finds the legal move of `game` described by `request`
+fn find_move(game: &Game, request: &MoveRequest) -> Result<Move, ApiError> {
+    if game.status().is_over() {
+        return Err(ApiError::GameOver);
+    }
+    let from = request.from;
+    let to = request.to;
+    let kills = request.kills.clone();
+
+    let mut candidates: Vec<Move> = game
+        .legal_moves()
+        .into_iter()
+        .filter(|turn| turn.starting_position == from && turn.end_position == to)
+        .filter(|turn| match &kills {
+            Some(kills) => {
+                let mut wanted = Move::from(from);
+                wanted.end_position = to;
+                wanted.kills = kills.clone();
+                turn.is_equivalent(&wanted)
+            }
+            None => true,
+        })
+        .collect();
+
+    match candidates.len() {
+        0 => Err(ApiError::IllegalMove),
+        1 => Ok(candidates.remove(0)),
+        _ => Err(ApiError::AmbiguousMove),
+    }
+}
+
+/// A game together with the live channels watching it
+#[derive(Debug)]
+struct Entry {
+    game: Game,
+    engine: Option<Engine>,
+    listeners: Vec<Listener>,
+}
+
+impl Entry {
+    /// plays `turn`, which has to be legal, and tells every listener about it
+    fn play(&mut self, id: u64, turn: Move) -> GameView {
+        self.game
+            .play(&turn)
+            .expect("moves found among the legal ones can be played");
+        let view = GameView::new(id, &self.game);
+        self.broadcast(&Event::Move { r#move: turn });
+        self.broadcast(&Event::Board { game: view.clone() });
+        if view.status.is_over() {
+            self.broadcast(&Event::GameOver {
+                status: view.status,
+            });
+        }
+        view
+    }
+
+    /// returns the engine if it is to move
+    fn engine_to_move(&self) -> Option<Engine> {
+        self.engine
+            .filter(|engine| engine.color == self.game.turn() && !self.game.status().is_over())
+    }
+
+    /// sends `event` to every listener, forgetting the disconnected ones
+    fn broadcast(&mut self, event: &Event) {
+        self.listeners
+            .retain(|listener| listener.send(event.clone()).is_ok());
+    }
+}
+
+/// Receiving end of the events of a game
+pub type Listener = mpsc::UnboundedSender<Event>;
+
+/// Games of the server, shared between the requests
+#[derive(Debug, Clone, Default)]
+pub struct Store {
+    games: Arc<Mutex<HashMap<u64, Entry>>>,
+    next_id: Arc<AtomicU64>,
+    engine: Arc<EngineContext>,
+}
+
+impl Store {
+    /// returns an empty store whose engine plays with `engine`,
+    /// the endgames being analysed with its tablebase
+    pub fn with_engine(engine: EngineContext) -> Self {
+        Self {
+            engine: Arc::new(engine),
+            ..Store::default()
+        }
+    }
+
+    /// starts a new game of `game_type`, with `engine` playing one of its sides,
+    /// returns its id and initial state
+    pub fn create(&self, game_type: GameType, engine: Option<Engine>) -> (u64, Game) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let game = Game::with_game_type(game_type);
+        let entry = Entry {
+            game: game.clone(),
+            engine,
+            listeners: Vec::new(),
+        };
+        self.games
+            .lock()
+            .expect("game store poisoned")
+            .insert(id, entry);
+        (id, game)
+    }
+
+    /// runs `f` on the game with given `id`
+    pub fn with_game<F, T>(&self, id: u64, f: F) -> Result<T, ApiError>
+    where
+        F: FnOnce(&mut Game) -> Result<T, ApiError>,
+    {
+        let mut games = self.games.lock().expect("game store poisoned");
+        let entry = games.get_mut(&id).ok_or(ApiError::GameNotFound)?;
+        f(&mut entry.game)
+    }
+
+    /// plays the move described by `request` and tells every listener about it.
+    /// When `player` is given, the move is accepted only on its turn.
+    /// Moves of the side the engine plays are never accepted, they are left to `play_engine`.
+    ///
+    /// This is the single place validating moves, whichever way they come in.
+    pub fn play(
+        &self,
+        id: u64,
+        request: &MoveRequest,
+        player: Option<Color>,
+    ) -> Result<GameView, ApiError> {
+        let mut games = self.games.lock().expect("game store poisoned");
+        let entry = games.get_mut(&id).ok_or(ApiError::GameNotFound)?;
+        let other_turn = matches!(player, Some(player) if player != entry.game.turn());
+        if other_turn || entry.engine_to_move().is_some() {
+            return Err(ApiError::NotYourTurn);
+        }
+
+        let turn = find_move(&entry.game, request)?;
+        Ok(entry.play(id, turn))
+    }
+
+    /// lets the engine of the game with given `id` play if it is to move, returns the game
+    /// after its move. The search runs without holding the games, so call it from a thread
+    /// allowed to block; if the game changes meanwhile the move is dropped.
+    pub fn play_engine(&self, id: u64) -> Result<Option<GameView>, ApiError> {
+        let (engine, game) = {
+            let games = self.games.lock().expect("game store poisoned");
+            let entry = games.get(&id).ok_or(ApiError::GameNotFound)?;
+            match entry.engine_to_move() {
+                Some(engine) => (engine, entry.game.clone()),
+                None => return Ok(None),
+            }
+        };
+
+        let board = game.board();
+        let turn = match board.find_move_at(engine.color, engine.difficulty, &self.engine) {
+            Some(turn) => turn,
+            None => return Ok(None),
+        };
+
+        let mut games = self.games.lock().expect("game store poisoned");
+        let entry = games.get_mut(&id).ok_or(ApiError::GameNotFound)?;
+        if entry.game.ply() != game.ply() || entry.game.hash() != game.hash() {
+            return Ok(None);
+        }
+        Ok(Some(entry.play(id, turn)))
+    }
+
+    /// lets the engine of the game with given `id` answer on a blocking thread,
+    /// returns the game after its move
+    pub async fn engine_reply(&self, id: u64) -> Result<Option<GameView>, ApiError> {
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || store.play_engine(id))
+            .await
+            .expect("the engine search does not panic")
+    }
+
+    /// returns the analysis of the current position of the game with given `id`
+    pub fn analyse(&self, id: u64) -> Result<AnalysisView, ApiError> {
+        self.with_game(id, |game| {
+            let tablebase = self
+                .engine
+                .tablebase
+                .as_ref()
+                .and_then(|tablebase| game.board().probe(tablebase, game.turn()));
+            Ok(AnalysisView { tablebase })
+        })
+    }
+
+    /// registers `listener` for the events of the game with given `id`
+    /// and sends it the current board right away
+    pub fn subscribe(&self, id: u64, listener: Listener) -> Result<(), ApiError> {
+        let mut games = self.games.lock().expect("game store poisoned");
+        let entry = games.get_mut(&id).ok_or(ApiError::GameNotFound)?;
+        let event = Event::Board {
+            game: GameView::new(id, &entry.game),
+        };
+        if listener.send(event).is_ok() {
+            entry.listeners.push(listener);
+        }
+        Ok(())
+    }
+}
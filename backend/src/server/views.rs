@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+
+use crate::board::difficulty::Difficulty;
+use crate::board::piece::Color;
+use crate::board::position::Position;
+use crate::board::rules::GameType;
+use crate::board::status::GameStatus;
+use crate::board::tablebase::Outcome;
+use crate::board::turn::Move;
+use crate::board::Board;
+use crate::game::Game;
+use crate::server::error::ApiError;
+
+/// Game as sent to the clients
+#[derive(Debug, Clone, Serialize)]
+pub struct GameView {
+    pub id: u64,
+    pub game_type: GameType,
+    pub board: Board,
+    pub turn: Color,
+    pub status: GameStatus,
+}
+
+impl GameView {
+    pub fn new(id: u64, game: &Game) -> Self {
+        Self {
+            id,
+            game_type: game.game_type(),
+            board: game.board().clone(),
+            turn: game.turn(),
+            status: game.status(),
+        }
+    }
+}
+
+/// Analysis of the current position of a game, serialized as
+/// `{"tablebase": {"result": "win", "plies": 7}}`, the tablebase result being `null`
+/// when the server has none or the position has too many pieces
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalysisView {
+    pub tablebase: Option<Outcome>,
+}
+
+/// Engine playing one side of a game, `{"color": "black", "difficulty": "easy"}`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Engine {
+    pub color: Color,
+    pub difficulty: Difficulty,
+}
+
+/// Game requested by a client, `{}` or no body at all starting a game of international
+/// draughts between two players, `{"game_type": "english"}` one of English draughts
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct GameRequest {
+    #[serde(default)]
+    pub game_type: GameType,
+    #[serde(default)]
+    pub engine: Option<Engine>,
+}
+
+/// Move submitted by a client. `kills` is needed only when two captures
+/// between the same squares take different pieces.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MoveRequest {
+    pub from: Position,
+    pub to: Position,
+    #[serde(default)]
+    pub kills: Option<Vec<Position>>,
+}
+
+/// Message pushed to the live channel of a game,
+/// tagged with its `type`: `move`, `board`, `game_over` or `error`
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    Move { r#move: Move },
+    Board { game: GameView },
+    GameOver { status: GameStatus },
+    Error { error: String },
+}
+
+/// reads the `white` or `black` player name
+pub fn parse_player(name: &str) -> Result<Color, ApiError> {
+    match name {
+        "white" => Ok(Color::White),
+        "black" => Ok(Color::Black),
+        _ => Err(ApiError::UnknownPlayer),
+    }
+}